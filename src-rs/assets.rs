@@ -0,0 +1,811 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use percent_encoding::percent_decode_str;
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::ops::Range;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use wry::http::header::{
+    ACCEPT_RANGES, CACHE_CONTROL, CONTENT_LENGTH, CONTENT_RANGE, CONTENT_TYPE, ETAG, HOST,
+    IF_MODIFIED_SINCE, IF_NONE_MATCH, IF_RANGE, LAST_MODIFIED, RANGE,
+};
+use wry::http::{Method, Request, Response, StatusCode};
+use zip::{CompressionMethod, ZipArchive};
+
+/// A set of files that can be served by an [`AssetServer`].
+pub trait AssetSource: Send + Sync {
+    /// Looks up a file by its normalized relative path, e.g. `assets/app.js`.
+    fn lookup(&self, path: &str) -> Option<AssetEntry>;
+
+    /// Reads a byte range of a file previously returned by [`AssetSource::lookup`].
+    fn read(&self, asset: &AssetEntry, range: Range<u64>) -> std::io::Result<Vec<u8>>;
+}
+
+pub struct AssetEntry {
+    path: String,
+    length: u64,
+    modified: Option<SystemTime>,
+    etag: String,
+    /// The file opened by the lookup, so what is read is the file that was checked.
+    file: Option<File>,
+}
+
+/// Answers custom protocol requests from an [`AssetSource`].
+pub struct AssetServer {
+    source: Box<dyn AssetSource>,
+    host: Option<String>,
+    spa_fallback: bool,
+}
+
+impl AssetServer {
+    pub fn new(
+        source: impl AssetSource + 'static,
+        host: Option<String>,
+        spa_fallback: bool,
+    ) -> Self {
+        AssetServer {
+            source: Box::new(source),
+            host,
+            spa_fallback,
+        }
+    }
+
+    pub fn handle(&self, request: &Request<Vec<u8>>) -> Response<Vec<u8>> {
+        if request.method() != Method::GET && request.method() != Method::HEAD {
+            return empty_response(StatusCode::METHOD_NOT_ALLOWED);
+        }
+
+        if let Some(host) = &self.host {
+            let request_host = request
+                .uri()
+                .host()
+                .or_else(|| request.headers().get(HOST).and_then(|h| h.to_str().ok()));
+            if request_host != Some(host.as_str()) {
+                return empty_response(StatusCode::NOT_FOUND);
+            }
+        }
+
+        let Some(entry) = self.resolve(request.uri().path()) else {
+            return empty_response(StatusCode::NOT_FOUND);
+        };
+
+        match serve_entry(request, self.source.as_ref(), &entry) {
+            Ok(response) => response,
+            Err(_) => empty_response(StatusCode::INTERNAL_SERVER_ERROR),
+        }
+    }
+
+    fn resolve(&self, request_path: &str) -> Option<AssetEntry> {
+        let decoded_path = percent_decode_str(request_path).decode_utf8().ok()?;
+        let path = sanitize_path(&decoded_path)?;
+
+        if let Some(entry) = self.source.lookup(&path) {
+            return Some(entry);
+        }
+
+        if let Some(entry) = self.source.lookup(&format!("{path}/index.html")) {
+            return Some(entry);
+        }
+
+        // Client side routes like `/settings/profile` have no file extension, those are handed
+        // to the app entry point instead of failing with a 404.
+        if self.spa_fallback && Path::new(&path).extension().is_none() {
+            return self.source.lookup("index.html");
+        }
+
+        None
+    }
+}
+
+/// Serves the files of a directory on disk.
+pub struct AssetDirectory {
+    root: PathBuf,
+}
+
+impl AssetDirectory {
+    pub fn new(root: PathBuf) -> Self {
+        let root = root.canonicalize().unwrap_or(root);
+        AssetDirectory { root }
+    }
+}
+
+impl AssetSource for AssetDirectory {
+    fn lookup(&self, path: &str) -> Option<AssetEntry> {
+        let file_path = self.root.join(path).canonicalize().ok()?;
+        if !file_path.starts_with(&self.root) {
+            return None;
+        }
+
+        let file = File::open(file_path).ok()?;
+        let metadata = file.metadata().ok()?;
+        if !metadata.is_file() {
+            return None;
+        }
+
+        let modified = metadata.modified().ok();
+        let modified_nanos = modified
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .map(|duration| duration.as_nanos())
+            .unwrap_or_default();
+
+        Some(AssetEntry {
+            path: path.to_string(),
+            length: metadata.len(),
+            modified,
+            etag: format!("\"{:x}-{modified_nanos:x}\"", metadata.len()),
+            file: Some(file),
+        })
+    }
+
+    fn read(&self, asset: &AssetEntry, range: Range<u64>) -> std::io::Result<Vec<u8>> {
+        let mut file = asset.file.as_ref().ok_or(std::io::ErrorKind::NotFound)?;
+        file.seek(SeekFrom::Start(range.start))?;
+
+        let mut body = vec![0; (range.end - range.start) as usize];
+        file.read_exact(&mut body)?;
+        Ok(body)
+    }
+}
+
+// Compressed entries up to this size are kept decompressed while ranges of them are requested.
+const MAX_CACHED_ENTRY_LENGTH: u64 = 256 * 1024 * 1024;
+
+struct ArchiveEntry {
+    index: usize,
+    length: u64,
+    crc32: u32,
+    stored: bool,
+}
+
+/// Serves the files of a zip archive, entries are decompressed when they are requested.
+///
+/// Ranges of stored entries are read directly. Media played through range requests should be
+/// stored uncompressed, a compressed entry is decompressed once and kept for following ranges.
+pub struct AssetArchive {
+    archive: Mutex<ZipArchive<File>>,
+    entries: HashMap<String, ArchiveEntry>,
+    modified: Option<SystemTime>,
+    cached_entry: Mutex<Option<(usize, Arc<Vec<u8>>)>>,
+}
+
+impl AssetArchive {
+    /// Opens the archive at `path`. When `integrity` is given as a subresource integrity string
+    /// (e.g. `sha256-<base64>`), the archive is only mounted if one of its hashes matches.
+    pub fn open(path: &Path, integrity: Option<&str>) -> std::io::Result<Self> {
+        // The handle that was hashed is the one that is read, so the file can't be swapped after
+        // it has been verified.
+        let mut file = File::open(path)?;
+        if let Some(integrity) = integrity {
+            verify_integrity(&mut file, path, integrity)?;
+        }
+
+        let modified = file.metadata()?.modified().ok();
+        let mut archive = ZipArchive::new(file).map_err(std::io::Error::other)?;
+
+        let mut entries = HashMap::new();
+        for index in 0..archive.len() {
+            let file = archive.by_index_raw(index).map_err(std::io::Error::other)?;
+            if !file.is_file() {
+                continue;
+            }
+
+            let Some(name) = sanitize_path(file.name()) else {
+                continue;
+            };
+
+            entries.insert(
+                name,
+                ArchiveEntry {
+                    index,
+                    length: file.size(),
+                    crc32: file.crc32(),
+                    stored: file.compression() == CompressionMethod::Stored,
+                },
+            );
+        }
+
+        Ok(AssetArchive {
+            archive: Mutex::new(archive),
+            entries,
+            modified,
+            cached_entry: Mutex::new(None),
+        })
+    }
+}
+
+impl AssetSource for AssetArchive {
+    fn lookup(&self, path: &str) -> Option<AssetEntry> {
+        let entry = self.entries.get(path)?;
+        Some(AssetEntry {
+            path: path.to_string(),
+            length: entry.length,
+            modified: self.modified,
+            etag: format!("\"{:08x}-{:x}\"", entry.crc32, entry.length),
+            file: None,
+        })
+    }
+
+    fn read(&self, asset: &AssetEntry, range: Range<u64>) -> std::io::Result<Vec<u8>> {
+        let entry = self
+            .entries
+            .get(&asset.path)
+            .ok_or(std::io::ErrorKind::NotFound)?;
+        let mut body = vec![0; (range.end - range.start) as usize];
+
+        if entry.stored {
+            let mut archive = self.archive.lock().unwrap();
+            let mut file = archive
+                .by_index_seek(entry.index)
+                .map_err(std::io::Error::other)?;
+            file.seek(SeekFrom::Start(range.start))?;
+            file.read_exact(&mut body)?;
+            return Ok(body);
+        }
+
+        let is_partial = range.start != 0 || range.end != entry.length;
+        if is_partial && entry.length <= MAX_CACHED_ENTRY_LENGTH {
+            let data = self.decompressed_entry(entry)?;
+            body.copy_from_slice(&data[range.start as usize..range.end as usize]);
+            return Ok(body);
+        }
+
+        let mut archive = self.archive.lock().unwrap();
+        let mut file = archive
+            .by_index(entry.index)
+            .map_err(std::io::Error::other)?;
+
+        // Compressed entries can not seek, the bytes before the range are decompressed and skipped.
+        std::io::copy(&mut (&mut file).take(range.start), &mut std::io::sink())?;
+        file.read_exact(&mut body)?;
+        Ok(body)
+    }
+}
+
+impl AssetArchive {
+    // Media elements request many ranges of the same entry in a row, only the last one is kept.
+    fn decompressed_entry(&self, entry: &ArchiveEntry) -> std::io::Result<Arc<Vec<u8>>> {
+        if let Some((index, data)) = self.cached_entry.lock().unwrap().as_ref()
+            && *index == entry.index
+        {
+            return Ok(data.clone());
+        }
+
+        let mut data = Vec::with_capacity(entry.length as usize);
+        {
+            let mut archive = self.archive.lock().unwrap();
+            let mut file = archive
+                .by_index(entry.index)
+                .map_err(std::io::Error::other)?;
+            file.read_to_end(&mut data)?;
+        }
+        if data.len() as u64 != entry.length {
+            return Err(std::io::ErrorKind::UnexpectedEof.into());
+        }
+
+        let data = Arc::new(data);
+        *self.cached_entry.lock().unwrap() = Some((entry.index, data.clone()));
+        Ok(data)
+    }
+}
+
+fn verify_integrity(file: &mut File, path: &Path, integrity: &str) -> std::io::Result<()> {
+    for expected in integrity.split_whitespace() {
+        let Some((algorithm, expected_hash)) = expected.split_once('-') else {
+            continue;
+        };
+
+        file.seek(SeekFrom::Start(0))?;
+        let hash = match algorithm {
+            "sha256" => file_digest::<Sha256>(file)?,
+            "sha384" => file_digest::<Sha384>(file)?,
+            "sha512" => file_digest::<Sha512>(file)?,
+            _ => continue,
+        };
+
+        if BASE64.encode(hash) == expected_hash {
+            file.seek(SeekFrom::Start(0))?;
+            return Ok(());
+        }
+    }
+
+    Err(std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        format!("Integrity check failed for {}", path.display()),
+    ))
+}
+
+fn file_digest<D: Digest + std::io::Write>(file: &mut File) -> std::io::Result<Vec<u8>> {
+    let mut hasher = D::new();
+    std::io::copy(file, &mut hasher)?;
+    Ok(hasher.finalize().to_vec())
+}
+
+/// Turns a request path into a normalized relative path, rejecting anything that could escape
+/// the asset root.
+fn sanitize_path(request_path: &str) -> Option<String> {
+    let mut segments = Vec::new();
+    for segment in request_path.split('/') {
+        if segment.is_empty() || segment == "." {
+            continue;
+        }
+
+        let mut components = Path::new(segment).components();
+        match (components.next(), components.next()) {
+            (Some(Component::Normal(_)), None) => segments.push(segment),
+            _ => return None,
+        }
+    }
+
+    if segments.is_empty() {
+        segments.push("index.html");
+    }
+
+    Some(segments.join("/"))
+}
+
+fn serve_entry(
+    request: &Request<Vec<u8>>,
+    source: &dyn AssetSource,
+    asset: &AssetEntry,
+) -> std::io::Result<Response<Vec<u8>>> {
+    let etag = &asset.etag;
+    let last_modified = asset.modified.map(httpdate::fmt_http_date);
+    let mime_type = mime_guess::from_path(&asset.path).first_or_octet_stream();
+
+    let builder = Response::builder()
+        .header(CONTENT_TYPE, mime_type.as_ref())
+        .header(ACCEPT_RANGES, "bytes")
+        .header(CACHE_CONTROL, "no-cache")
+        .header(ETAG, etag);
+    let builder = match &last_modified {
+        Some(last_modified) => builder.header(LAST_MODIFIED, last_modified),
+        None => builder,
+    };
+
+    if is_not_modified(request, etag, asset.modified) {
+        return Ok(builder
+            .status(StatusCode::NOT_MODIFIED)
+            .body(Vec::new())
+            .unwrap());
+    }
+
+    let range = match requested_range(request, etag, asset.length) {
+        RequestedRange::Full => None,
+        RequestedRange::Partial(range) => Some(range),
+        RequestedRange::Unsatisfiable => {
+            return Ok(builder
+                .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                .header(CONTENT_RANGE, format!("bytes */{}", asset.length))
+                .body(Vec::new())
+                .unwrap());
+        }
+    };
+
+    let (status, byte_range) = match range {
+        Some(range) => (StatusCode::PARTIAL_CONTENT, range),
+        None => (StatusCode::OK, 0..asset.length),
+    };
+
+    let mut builder = builder
+        .status(status)
+        .header(CONTENT_LENGTH, byte_range.end - byte_range.start);
+    if status == StatusCode::PARTIAL_CONTENT {
+        builder = builder.header(
+            CONTENT_RANGE,
+            format!(
+                "bytes {}-{}/{}",
+                byte_range.start,
+                byte_range.end - 1,
+                asset.length
+            ),
+        );
+    }
+
+    let body = if request.method() == Method::HEAD {
+        Vec::new()
+    } else {
+        source.read(asset, byte_range)?
+    };
+
+    Ok(builder.body(body).unwrap())
+}
+
+fn is_not_modified(request: &Request<Vec<u8>>, etag: &str, modified: Option<SystemTime>) -> bool {
+    let headers = request.headers();
+
+    // If-None-Match takes precedence over If-Modified-Since when both are sent.
+    if let Some(if_none_match) = headers.get(IF_NONE_MATCH).and_then(|h| h.to_str().ok()) {
+        return if_none_match
+            .split(',')
+            .map(|tag| tag.trim().trim_start_matches("W/"))
+            .any(|tag| tag == "*" || tag == etag);
+    }
+
+    let if_modified_since = headers
+        .get(IF_MODIFIED_SINCE)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| httpdate::parse_http_date(h).ok());
+    match (if_modified_since, modified) {
+        (Some(since), Some(modified)) => {
+            // HTTP dates only have second precision.
+            let modified = modified
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or_default();
+            let since = since
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or_default();
+            modified <= since
+        }
+        _ => false,
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum RequestedRange {
+    Full,
+    Partial(Range<u64>),
+    Unsatisfiable,
+}
+
+fn requested_range(request: &Request<Vec<u8>>, etag: &str, length: u64) -> RequestedRange {
+    let headers = request.headers();
+    let Some(range) = headers.get(RANGE).and_then(|h| h.to_str().ok()) else {
+        return RequestedRange::Full;
+    };
+
+    // A stale If-Range means the client's partial copy is outdated, so send everything.
+    if let Some(if_range) = headers.get(IF_RANGE).and_then(|h| h.to_str().ok())
+        && if_range.trim() != etag
+    {
+        return RequestedRange::Full;
+    }
+
+    let Some(range) = range.trim().strip_prefix("bytes=") else {
+        return RequestedRange::Full;
+    };
+
+    // Multipart ranges are not supported, answering with the full body is allowed.
+    if range.contains(',') {
+        return RequestedRange::Full;
+    }
+
+    // A malformed range is ignored rather than rejected, only a range past the end is a 416.
+    let Some((start, end)) = range.split_once('-') else {
+        return RequestedRange::Full;
+    };
+    let (start, end) = (start.trim(), end.trim());
+
+    let byte_range = if start.is_empty() {
+        match end.parse::<u64>() {
+            Ok(0) => return RequestedRange::Unsatisfiable,
+            Ok(suffix) => length.saturating_sub(suffix)..length,
+            Err(_) => return RequestedRange::Full,
+        }
+    } else {
+        let Ok(start) = start.parse::<u64>() else {
+            return RequestedRange::Full;
+        };
+        let end = match end {
+            "" => length,
+            end => match end.parse::<u64>() {
+                Ok(end) if end >= start => end.saturating_add(1).min(length),
+                _ => return RequestedRange::Full,
+            },
+        };
+        start..end
+    };
+
+    if byte_range.start >= byte_range.end {
+        return RequestedRange::Unsatisfiable;
+    }
+
+    RequestedRange::Partial(byte_range)
+}
+
+fn empty_response(status: StatusCode) -> Response<Vec<u8>> {
+    Response::builder().status(status).body(Vec::new()).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct MemorySource(HashMap<&'static str, &'static [u8]>);
+
+    impl AssetSource for MemorySource {
+        fn lookup(&self, path: &str) -> Option<AssetEntry> {
+            let body = self.0.get(path)?;
+            Some(AssetEntry {
+                path: path.to_string(),
+                length: body.len() as u64,
+                modified: None,
+                etag: "\"etag\"".to_string(),
+                file: None,
+            })
+        }
+
+        fn read(&self, asset: &AssetEntry, range: Range<u64>) -> std::io::Result<Vec<u8>> {
+            let body = self
+                .0
+                .get(asset.path.as_str())
+                .ok_or(std::io::ErrorKind::NotFound)?;
+            Ok(body[range.start as usize..range.end as usize].to_vec())
+        }
+    }
+
+    fn server() -> AssetServer {
+        let files = HashMap::from([
+            ("index.html", b"<h1>index</h1>".as_slice()),
+            ("app/main.js", b"main()".as_slice()),
+        ]);
+        AssetServer::new(MemorySource(files), None, false)
+    }
+
+    fn range_request(range: &str) -> Request<Vec<u8>> {
+        Request::builder()
+            .uri("app://localhost/video.mp4")
+            .header(RANGE, range)
+            .body(Vec::new())
+            .unwrap()
+    }
+
+    fn script() -> Vec<u8> {
+        (0..2000)
+            .flat_map(|i| format!("{i};").into_bytes())
+            .collect()
+    }
+
+    // Each test writes its own archive, tests run in parallel.
+    fn write_archive(name: &str) -> PathBuf {
+        use std::io::Write;
+        use zip::write::SimpleFileOptions;
+
+        let path = std::env::temp_dir().join(format!("rod-{name}-{}.zip", std::process::id()));
+        let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+        let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+        let mut writer = zip::ZipWriter::new(File::create(&path).unwrap());
+        writer.start_file("index.html", deflated).unwrap();
+        writer.write_all(b"<h1>index</h1>").unwrap();
+        writer.start_file("app/main.js", deflated).unwrap();
+        writer.write_all(&script()).unwrap();
+        writer.start_file("media/video.mp4", stored).unwrap();
+        writer.write_all(&(0..=255).collect::<Vec<u8>>()).unwrap();
+        writer.finish().unwrap();
+        path
+    }
+
+    fn integrity(path: &Path) -> String {
+        let hash = Sha256::digest(std::fs::read(path).unwrap());
+        format!("sha256-{}", BASE64.encode(hash))
+    }
+
+    #[test]
+    fn archive_serves_entries() {
+        let path = write_archive("entries");
+        let archive = AssetArchive::open(&path, None).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        let server = AssetServer::new(archive, None, false);
+        let request = Request::builder()
+            .uri("app://localhost/")
+            .body(Vec::new())
+            .unwrap();
+        let response = server.handle(&request);
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.body(), b"<h1>index</h1>");
+        assert!(server.resolve("/missing.js").is_none());
+    }
+
+    #[test]
+    fn archive_reads_ranges_of_compressed_entries() {
+        let path = write_archive("ranges");
+        let archive = AssetArchive::open(&path, None).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        let script = script();
+        let length = script.len() as u64;
+        assert_eq!(archive.lookup("app/main.js").unwrap().length, length);
+        assert_eq!(
+            archive
+                .read(&archive.lookup("app/main.js").unwrap(), 100..200)
+                .unwrap(),
+            &script[100..200]
+        );
+        assert_eq!(
+            archive
+                .read(&archive.lookup("app/main.js").unwrap(), 10..20)
+                .unwrap(),
+            &script[10..20]
+        );
+        assert_eq!(
+            archive
+                .read(&archive.lookup("app/main.js").unwrap(), 0..length)
+                .unwrap(),
+            script
+        );
+
+        let video = archive
+            .read(&archive.lookup("media/video.mp4").unwrap(), 16..32)
+            .unwrap();
+        assert_eq!(video, (16..32).collect::<Vec<u8>>());
+    }
+
+    // A file swapped after the lookup isn't read, the lookup's handle still points at the old one.
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn directory_reads_the_file_it_looked_up() {
+        let root = std::env::temp_dir().join(format!("rod-directory-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("index.html"), "inside").unwrap();
+        let directory = AssetDirectory::new(root.clone());
+
+        let entry = directory.lookup("index.html").unwrap();
+        std::fs::remove_file(root.join("index.html")).unwrap();
+        std::fs::write(root.join("index.html"), "swapped").unwrap();
+        let body = directory.read(&entry, 0..entry.length);
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(body.unwrap(), b"inside");
+    }
+
+    #[test]
+    fn archive_accepts_matching_integrity() {
+        let path = write_archive("integrity");
+        let integrity = integrity(&path);
+        let other = "sha512-AAAA";
+
+        assert!(AssetArchive::open(&path, Some(&integrity)).is_ok());
+        assert!(AssetArchive::open(&path, Some(&format!("{other} {integrity}"))).is_ok());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn archive_rejects_integrity_mismatch() {
+        let path = write_archive("mismatch");
+        let mismatch = |integrity: &str| {
+            AssetArchive::open(&path, Some(integrity))
+                .err()
+                .map(|error| error.kind())
+        };
+
+        let hash = BASE64.encode(Sha256::digest(b"other"));
+        assert_eq!(
+            mismatch(&format!("sha256-{hash}")),
+            Some(std::io::ErrorKind::InvalidData)
+        );
+        assert_eq!(mismatch("md5-AAAA"), Some(std::io::ErrorKind::InvalidData));
+        assert_eq!(mismatch(""), Some(std::io::ErrorKind::InvalidData));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn sanitize_path_normalizes_segments() {
+        assert_eq!(sanitize_path("/"), Some("index.html".to_string()));
+        assert_eq!(sanitize_path(""), Some("index.html".to_string()));
+        assert_eq!(
+            sanitize_path("/app//main.js"),
+            Some("app/main.js".to_string())
+        );
+        assert_eq!(
+            sanitize_path("/./app/./main.js"),
+            Some("app/main.js".to_string())
+        );
+    }
+
+    #[test]
+    fn sanitize_path_rejects_traversal() {
+        assert_eq!(sanitize_path("/.."), None);
+        assert_eq!(sanitize_path("/../secret.txt"), None);
+        assert_eq!(sanitize_path("/app/../../secret.txt"), None);
+        assert_eq!(sanitize_path("/app/../index.html"), None);
+    }
+
+    #[test]
+    fn resolve_rejects_encoded_traversal() {
+        let server = server();
+        assert!(server.resolve("/app/main.js").is_some());
+        assert!(server.resolve("/%2e%2e/secret.txt").is_none());
+        assert!(server.resolve("/app/%2E%2E/%2e%2e/secret.txt").is_none());
+        assert!(server.resolve("/app%2f..%2f..%2fsecret.txt").is_none());
+        assert!(server.resolve("/%ff").is_none());
+    }
+
+    #[test]
+    fn resolve_serves_index_for_directories() {
+        let server = server();
+        assert_eq!(
+            server.resolve("/").map(|entry| entry.path),
+            Some("index.html".to_string())
+        );
+        assert!(server.resolve("/settings").is_none());
+
+        let files = HashMap::from([("index.html", b"".as_slice())]);
+        let spa = AssetServer::new(MemorySource(files), None, true);
+        assert!(spa.resolve("/settings/profile").is_some());
+        assert!(spa.resolve("/missing.js").is_none());
+    }
+
+    #[test]
+    fn requested_range_parses_single_ranges() {
+        let range = |header| requested_range(&range_request(header), "\"etag\"", 100);
+        assert_eq!(range("bytes=0-9"), RequestedRange::Partial(0..10));
+        assert_eq!(range("bytes=90-"), RequestedRange::Partial(90..100));
+        assert_eq!(range("bytes=-10"), RequestedRange::Partial(90..100));
+        assert_eq!(range("bytes=-500"), RequestedRange::Partial(0..100));
+        assert_eq!(range("bytes=50-500"), RequestedRange::Partial(50..100));
+        assert_eq!(range(" bytes= 5 - 6 "), RequestedRange::Partial(5..7));
+    }
+
+    #[test]
+    fn requested_range_rejects_ranges_past_the_end() {
+        let range = |header| requested_range(&range_request(header), "\"etag\"", 100);
+        assert_eq!(range("bytes=100-"), RequestedRange::Unsatisfiable);
+        assert_eq!(range("bytes=200-300"), RequestedRange::Unsatisfiable);
+        assert_eq!(range("bytes=-0"), RequestedRange::Unsatisfiable);
+    }
+
+    #[test]
+    fn requested_range_ignores_malformed_headers() {
+        let range = |header| requested_range(&range_request(header), "\"etag\"", 100);
+        assert_eq!(range("bytes=abc-"), RequestedRange::Full);
+        assert_eq!(range("bytes=0-abc"), RequestedRange::Full);
+        assert_eq!(range("bytes=-abc"), RequestedRange::Full);
+        assert_eq!(range("bytes=10-5"), RequestedRange::Full);
+        assert_eq!(range("bytes=5"), RequestedRange::Full);
+        assert_eq!(range("bytes=-"), RequestedRange::Full);
+        assert_eq!(range("items=0-9"), RequestedRange::Full);
+        assert_eq!(range(""), RequestedRange::Full);
+    }
+
+    #[test]
+    fn requested_range_ignores_multiple_ranges() {
+        let range = |header| requested_range(&range_request(header), "\"etag\"", 100);
+        assert_eq!(range("bytes=0-9,20-29"), RequestedRange::Full);
+        assert_eq!(range("bytes=0-9, -10"), RequestedRange::Full);
+    }
+
+    #[test]
+    fn requested_range_honors_if_range() {
+        let request = |if_range| {
+            Request::builder()
+                .header(RANGE, "bytes=0-9")
+                .header(IF_RANGE, if_range)
+                .body(Vec::new())
+                .unwrap()
+        };
+        let fresh = requested_range(&request("\"etag\""), "\"etag\"", 100);
+        assert_eq!(fresh, RequestedRange::Partial(0..10));
+        let stale = requested_range(&request("\"old\""), "\"etag\"", 100);
+        assert_eq!(stale, RequestedRange::Full);
+    }
+
+    #[test]
+    fn handle_answers_ranges() {
+        let server = server();
+        let request = Request::builder()
+            .uri("app://localhost/app/main.js")
+            .header(RANGE, "bytes=0-3")
+            .body(Vec::new())
+            .unwrap();
+        let response = server.handle(&request);
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(response.body(), b"main");
+
+        let request = Request::builder()
+            .uri("app://localhost/app/main.js")
+            .header(RANGE, "bytes=100-")
+            .body(Vec::new())
+            .unwrap();
+        assert_eq!(
+            server.handle(&request).status(),
+            StatusCode::RANGE_NOT_SATISFIABLE
+        );
+    }
+}
@@ -1,19 +1,30 @@
+mod assets;
+mod navigation;
+
+use assets::{AssetArchive, AssetDirectory, AssetServer};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use navigation::NavigationPolicy;
 use serde_json::{Value, json};
+use std::cell::Cell;
 use std::collections::HashMap;
 use std::ffi::CStr;
 use std::ffi::CString;
 use std::ffi::c_char;
 use std::ffi::c_void;
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
+use std::rc::Rc;
+use std::sync::Arc;
 use std::sync::LazyLock;
 use std::sync::Mutex;
+use std::sync::atomic::{AtomicU32, Ordering};
 use tao::dpi::LogicalPosition;
 use tao::dpi::LogicalSize;
 use tao::dpi::PhysicalPosition;
 use tao::dpi::PhysicalSize;
 use tao::event::Event;
 use tao::event::WindowEvent;
-use tao::event_loop::{ControlFlow, EventLoop, EventLoopBuilder};
+use tao::event_loop::{ControlFlow, EventLoop, EventLoopBuilder, EventLoopProxy};
 use tao::window::Fullscreen::Borderless;
 use tao::window::ProgressBarState;
 use tao::window::ProgressState;
@@ -25,9 +36,17 @@ use tray_icon::TrayIcon;
 use tray_icon::TrayIconBuilder;
 use tray_icon::TrayIconEvent;
 use tray_icon::menu::Menu;
+use wry::NewWindowResponse;
+use wry::PageLoadEvent;
+use wry::RequestAsyncResponder;
 use wry::WebContext;
 use wry::WebView;
 use wry::WebViewBuilder;
+use wry::cookie::time::OffsetDateTime;
+use wry::cookie::{Cookie, SameSite};
+use wry::http::Response;
+use wry::http::{HeaderMap, HeaderName, HeaderValue};
+use wry::{ProxyConfig, ProxyEndpoint};
 
 fn init_runtime_env() {
     unsafe {
@@ -39,9 +58,273 @@ fn init_runtime_env() {
 static WINDOW_ID_MAP: LazyLock<Mutex<HashMap<WindowId, u16>>> =
     LazyLock::new(|| Mutex::new(HashMap::new()));
 
+const INVOKE_MESSAGE_PREFIX: &str = "__rod_invoke:";
+
+// Exposes `window.rod.invoke(command, args, options)` to the page. Calls are posted over the IPC
+// channel with a correlation id and settled by `rod_webview_resolve_invoke`.
+const INVOKE_SCRIPT: &str = r#"(function () {
+    if (window.rod) return;
+
+    const pending = new Map();
+    let nextCallId = 1;
+
+    function settle(callId) {
+        const call = pending.get(callId);
+        if (!call) return undefined;
+        pending.delete(callId);
+        clearTimeout(call.timeout);
+        return call;
+    }
+
+    Object.defineProperty(window, "__rod_resolve_invoke", {
+        value: function (callId, payload, isError) {
+            const call = settle(callId);
+            if (!call) return;
+            if (!isError) return call.resolve(payload);
+            const message = payload && payload.message ? payload.message : String(payload);
+            call.reject(Object.assign(new Error(message), { data: payload }));
+        },
+    });
+
+    Object.defineProperty(window, "rod", {
+        value: Object.freeze({
+            invoke: function (command, args, options) {
+                return new Promise(function (resolve, reject) {
+                    // Call ids are u32 on the native side, they wrap around before overflowing.
+                    const callId = nextCallId;
+                    nextCallId = nextCallId >= 0xffffffff ? 1 : nextCallId + 1;
+                    const call = { resolve: resolve, reject: reject, timeout: undefined };
+                    const timeout = options && options.timeout;
+                    if (timeout > 0) {
+                        call.timeout = setTimeout(function () {
+                            if (settle(callId)) {
+                                reject(new Error("Invoke '" + command + "' timed out after " + timeout + "ms"));
+                            }
+                        }, timeout);
+                    }
+                    pending.set(callId, call);
+
+                    try {
+                        window.ipc.postMessage("__rod_invoke:" + JSON.stringify({
+                            id: callId,
+                            command: command,
+                            args: args === undefined ? null : args,
+                        }));
+                    } catch (error) {
+                        settle(callId);
+                        reject(error);
+                    }
+                });
+            },
+        }),
+    });
+})();"#;
+
+const EVAL_MESSAGE_PREFIX: &str = "__rod_eval:";
+
+// Wraps a script passed to `rod_webview_eval_with_callback` so exceptions are reported back
+// instead of being swallowed by the platform webview. Platform webviews don't await promises, a
+// thenable result is posted over the IPC channel once it settles. Results go through JSON first,
+// so values that can't be serialized fail instead of arriving as null.
+const EVAL_WRAPPER_SCRIPT: &str = r#"(function () {
+    function serialize(value) {
+        try {
+            const json = JSON.stringify(value);
+            return { value: json === undefined ? null : JSON.parse(json), error: null };
+        } catch (error) {
+            return { value: null, error: "Failed to serialize result: " + String(error) };
+        }
+    }
+
+    function post(result) {
+        result.id = __ROD_REQUEST_ID__;
+        window.ipc.postMessage("__rod_eval:" + JSON.stringify(result));
+    }
+
+    try {
+        const value = (0, eval)(__ROD_SCRIPT__);
+        const isObject = value !== null && (typeof value === "object" || typeof value === "function");
+        if (!isObject || typeof value.then !== "function") return serialize(value);
+
+        Promise.resolve(value).then(
+            function (value) { post(serialize(value)); },
+            function (error) { post({ value: null, error: String(error) }); }
+        );
+        return { pending: true };
+    } catch (error) {
+        return { value: null, error: String(error) };
+    }
+})()"#;
+
+static WEBVIEW_ID_MAP: LazyLock<Mutex<HashMap<usize, u16>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+static WEBVIEW_WINDOW_MAP: LazyLock<Mutex<HashMap<usize, usize>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+static WEBVIEW_WEBCONTEXT_MAP: LazyLock<Mutex<HashMap<usize, usize>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+static WEBCONTEXT_REFERENCE_MAP: LazyLock<Mutex<HashMap<usize, usize>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+static PROFILE_MAP: LazyLock<Mutex<HashMap<PathBuf, usize>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+static PROTOCOL_RESPONDER_MAP: LazyLock<Mutex<HashMap<u32, (u16, RequestAsyncResponder)>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// The Accept-Language each web context was last set to.
+static WEBCONTEXT_LANGUAGE_MAP: LazyLock<Mutex<HashMap<usize, String>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// The temporary user data folder of each incognito web context, removed with the context.
+#[cfg(target_os = "windows")]
+static INCOGNITO_DIRECTORY_MAP: LazyLock<Mutex<HashMap<usize, PathBuf>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// The proxy URI each web context was set up with, None for the system proxy.
+static WEBCONTEXT_PROXY_MAP: LazyLock<Mutex<HashMap<usize, Option<String>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// Keyed by webview id and protocol.
+type AssetServerMap = HashMap<(u16, String), Arc<AssetServer>>;
+
+static ASSET_SERVER_MAP: LazyLock<Mutex<AssetServerMap>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+static PROTOCOL_REQUEST_INDEX: AtomicU32 = AtomicU32::new(1);
+
+#[cfg(target_os = "windows")]
+static INCOGNITO_INDEX: AtomicU32 = AtomicU32::new(1);
+
+type DecisionCallback = extern "C" fn(event: *const c_char, data: *const c_char) -> *const c_char;
+
+static DECISION_CALLBACK: LazyLock<Mutex<Option<DecisionCallback>>> =
+    LazyLock::new(|| Mutex::new(None));
+
+static EVENT_LOOP_PROXY: LazyLock<Mutex<Option<EventLoopProxy<CustomEvent>>>> =
+    LazyLock::new(|| Mutex::new(None));
+
+static LAST_ERROR: LazyLock<Mutex<Option<String>>> = LazyLock::new(|| Mutex::new(None));
+
 enum CustomEvent {
     TrayIconEvent(tray_icon::TrayIconEvent),
+    WebViewIpcMessage {
+        webview_id: u16,
+        url: String,
+        message: String,
+    },
+    WebViewInvoke {
+        webview_id: u16,
+        call_id: u32,
+        command: String,
+        args: Value,
+    },
+    WebViewEvalResult {
+        webview_id: u16,
+        request_id: u32,
+        result: Value,
+    },
+    ProtocolRequest {
+        webview_id: u16,
+        request_id: u32,
+        protocol: String,
+        method: String,
+        url: String,
+        headers: Value,
+        body: String,
+    },
+    WebViewPageLoad {
+        webview_id: u16,
+        event: PageLoadEvent,
+        url: String,
+        history: Option<(bool, bool)>,
+    },
+    WebViewNewWindowRequested {
+        webview_id: u16,
+        url: String,
+        size: Option<LogicalSize<f64>>,
+        position: Option<LogicalPosition<f64>>,
+    },
+    WebViewTitleChanged {
+        webview_id: u16,
+        title: String,
+    },
+    WebViewDownloadProgress {
+        webview_id: u16,
+        url: String,
+        received_bytes: u64,
+        total_bytes: u64,
+        progress: f64,
+    },
+    WebViewDownloadCompleted {
+        webview_id: u16,
+        url: String,
+        path: Option<PathBuf>,
+        success: bool,
+    },
+    WebContextDataCleared {
+        request_id: u32,
+        success: bool,
+    },
+    WebViewSnapshot {
+        webview_id: u16,
+        request_id: u32,
+        path: Option<String>,
+        result: Result<Option<Vec<u8>>, String>,
+    },
+    WebViewPdfPrinted {
+        webview_id: u16,
+        request_id: u32,
+        path: String,
+        error: Option<String>,
+    },
+}
+
+//#region Last error
+// Exports that fail by returning null record why, Bun reads it with `rod_take_last_error`.
+fn set_last_error(message: String) {
+    let mut last_error = LAST_ERROR.lock().unwrap();
+    *last_error = Some(message);
+}
+//#endregion
+
+//#region Event loop proxy
+fn set_event_loop_proxy(proxy: EventLoopProxy<CustomEvent>) {
+    let mut event_loop_proxy = EVENT_LOOP_PROXY.lock().unwrap();
+    *event_loop_proxy = Some(proxy);
+}
+
+fn send_custom_event(event: CustomEvent) {
+    let event_loop_proxy = EVENT_LOOP_PROXY.lock().unwrap();
+    if let Some(proxy) = event_loop_proxy.as_ref() {
+        proxy.send_event(event).ok();
+    }
+}
+//#endregion
+
+//#region Decisions
+// Unlike events, decisions are answered synchronously by Bun. Webview handlers run on the thread
+// that polls the event loop, so the callback can be invoked directly.
+fn request_decision(event_name: &str, data_json: &Value) -> Value {
+    let Some(callback) = *DECISION_CALLBACK.lock().unwrap() else {
+        return Value::Null;
+    };
+
+    let event_c = string_to_ptr(event_name);
+    let data_c = string_to_ptr(data_json.to_string().as_str());
+    let response_ptr = callback(event_c.as_ptr(), data_c.as_ptr());
+    if response_ptr.is_null() {
+        return Value::Null;
+    }
+
+    let response = unsafe { CStr::from_ptr(response_ptr) }
+        .to_str()
+        .unwrap_or_default();
+    serde_json::from_str(response).unwrap_or_default()
 }
+//#endregion
 
 //#region Window ID map management
 pub fn insert_window_id(window_id: WindowId, custom_id: u16) {
@@ -60,766 +343,3742 @@ pub fn get_custom_window_id(window_id: &WindowId) -> Option<u16> {
 }
 //#endregion
 
-//#region icons
-fn load_tray_icon(path: &std::path::Path) -> tray_icon::Icon {
-    let (icon_rgba, icon_width, icon_height) = {
-        let image = image::open(path)
-            .expect("Failed to open icon path")
-            .into_rgba8();
-        let (width, height) = image.dimensions();
-        let rgba = image.into_raw();
-        (rgba, width, height)
-    };
-    tray_icon::Icon::from_rgba(icon_rgba, icon_width, icon_height).expect("Failed to open icon")
+//#region Webview ID map management
+fn insert_webview_id(webview_ptr: *mut c_void, webview_id: u16) {
+    let mut map = WEBVIEW_ID_MAP.lock().unwrap();
+    map.insert(webview_ptr as usize, webview_id);
 }
-//#endregion
 
-//#region Pointer conversions
-fn string_from_ptr(string_ptr: *mut c_void) -> String {
-    unsafe { CStr::from_ptr(string_ptr as *const c_char) }
-        .to_str()
-        .unwrap_or_default()
-        .to_string()
+fn remove_webview_id(webview_ptr: *mut c_void) {
+    let mut map = WEBVIEW_ID_MAP.lock().unwrap();
+    map.remove(&(webview_ptr as usize));
 }
 
-fn string_to_ptr(string: &str) -> CString {
-    return CString::new(string).unwrap();
+fn get_webview_id(webview_ptr: *mut c_void) -> Option<u16> {
+    let map = WEBVIEW_ID_MAP.lock().unwrap();
+    map.get(&(webview_ptr as usize)).copied()
 }
 
-fn event_loop_from_ptr(event_loop_ptr: *mut c_void) -> &'static mut EventLoop<CustomEvent> {
-    unsafe { &mut *(event_loop_ptr as *mut EventLoop<CustomEvent>) }
+fn get_webview_by_id(webview_id: u16) -> Option<*mut c_void> {
+    let map = WEBVIEW_ID_MAP.lock().unwrap();
+    map.iter()
+        .find(|(_, id)| **id == webview_id)
+        .map(|(webview_ptr, _)| *webview_ptr as *mut c_void)
 }
+//#endregion
 
-fn event_loop_to_ptr(event_loop: EventLoop<CustomEvent>) -> *mut c_void {
-    Box::into_raw(Box::new(event_loop)) as *mut c_void
+//#region Webview window map
+// The window a webview currently lives in, it changes when the webview is reparented.
+fn insert_webview_window(webview_ptr: *mut c_void, window_ptr: *mut c_void) {
+    let mut map = WEBVIEW_WINDOW_MAP.lock().unwrap();
+    map.insert(webview_ptr as usize, window_ptr as usize);
 }
 
-fn window_from_ptr(window_ptr: *mut c_void) -> &'static mut Window {
-    unsafe { &mut *(window_ptr as *mut Window) }
+fn remove_webview_window(webview_ptr: *mut c_void) {
+    let mut map = WEBVIEW_WINDOW_MAP.lock().unwrap();
+    map.remove(&(webview_ptr as usize));
 }
 
-fn window_to_ptr(window: Window) -> *mut c_void {
-    Box::into_raw(Box::new(window)) as *mut c_void
+fn get_webview_window(webview_ptr: *mut c_void) -> Option<*mut c_void> {
+    let map = WEBVIEW_WINDOW_MAP.lock().unwrap();
+    map.get(&(webview_ptr as usize))
+        .map(|window| *window as *mut c_void)
 }
+//#endregion
 
-fn webcontext_from_ptr(webcontext_ptr: *mut c_void) -> &'static mut WebContext {
-    unsafe { &mut *(webcontext_ptr as *mut WebContext) }
+//#region Webview webcontext map
+// wry keeps the platform context private, its data is reached through a webview built with it.
+fn insert_webview_webcontext(webview_ptr: *mut c_void, webcontext_ptr: *mut c_void) {
+    let mut map = WEBVIEW_WEBCONTEXT_MAP.lock().unwrap();
+    map.insert(webview_ptr as usize, webcontext_ptr as usize);
 }
 
-fn webcontext_to_ptr(webcontext: WebContext) -> *mut c_void {
-    Box::into_raw(Box::new(webcontext)) as *mut c_void
+fn get_webview_webcontext(webview_ptr: *mut c_void) -> Option<*mut c_void> {
+    let map = WEBVIEW_WEBCONTEXT_MAP.lock().unwrap();
+    map.get(&(webview_ptr as usize))
+        .map(|webcontext| *webcontext as *mut c_void)
 }
 
-fn webview_from_ptr(webview_ptr: *mut c_void) -> &'static mut WebView {
-    unsafe { &mut *(webview_ptr as *mut WebView) }
+fn remove_webview_webcontext(webview_ptr: *mut c_void) -> Option<*mut c_void> {
+    let mut map = WEBVIEW_WEBCONTEXT_MAP.lock().unwrap();
+    map.remove(&(webview_ptr as usize))
+        .map(|webcontext| webcontext as *mut c_void)
 }
 
-fn webview_to_ptr(webview: WebView) -> *mut c_void {
-    Box::into_raw(Box::new(webview)) as *mut c_void
+fn get_webcontext_webview(webcontext_ptr: *mut c_void) -> Option<*mut c_void> {
+    let map = WEBVIEW_WEBCONTEXT_MAP.lock().unwrap();
+    map.iter()
+        .find(|(_, webcontext)| **webcontext == webcontext_ptr as usize)
+        .map(|(webview, _)| *webview as *mut c_void)
 }
+//#endregion
 
-// fn tray_from_ptr(tray_ptr: *mut c_void) -> &'static mut TrayIcon {
-//     unsafe { &mut *(tray_ptr as *mut TrayIcon) }
-// }
-
-fn tray_to_ptr(tray: TrayIcon) -> *mut c_void {
-    Box::into_raw(Box::new(tray)) as *mut c_void
+//#region Webcontext references
+// Webcontexts are shared between webviews, they are only freed once every webview using them and
+// the handle returned to Bun have been released.
+fn retain_webcontext(webcontext_ptr: *mut c_void) {
+    let mut map = WEBCONTEXT_REFERENCE_MAP.lock().unwrap();
+    *map.entry(webcontext_ptr as usize).or_insert(0) += 1;
 }
-//#endregion
 
-#[unsafe(no_mangle)]
-pub extern "C" fn rod_event_loop_create() -> *mut c_void {
-    init_runtime_env();
-    let event_loop = EventLoopBuilder::<CustomEvent>::with_user_event().build();
+fn release_webcontext(webcontext_ptr: *mut c_void) {
+    if !release_webcontext_reference(webcontext_ptr) {
+        return;
+    }
 
-    let proxy = event_loop.create_proxy();
-    TrayIconEvent::set_event_handler(Some(move |event| {
-        proxy.send_event(CustomEvent::TrayIconEvent(event)).ok();
-    }));
+    unsafe {
+        drop(Box::from_raw(webcontext_ptr as *mut WebContext));
+    }
 
-    return event_loop_to_ptr(event_loop);
+    #[cfg(target_os = "windows")]
+    {
+        let mut directories = INCOGNITO_DIRECTORY_MAP.lock().unwrap();
+        if let Some(directory) = directories.remove(&(webcontext_ptr as usize)) {
+            let _ = std::fs::remove_dir_all(directory);
+        }
+    }
 }
 
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_event_loop_destroy(event_loop_ptr: *mut c_void) {
-    unsafe {
-        drop(Box::from_raw(event_loop_ptr as *mut EventLoop<()>));
+// Drops one reference, returns whether it was the last one. The context is forgotten then, it's
+// left to the caller to free it.
+fn release_webcontext_reference(webcontext_ptr: *mut c_void) -> bool {
+    let mut map = WEBCONTEXT_REFERENCE_MAP.lock().unwrap();
+    let Some(references) = map.get_mut(&(webcontext_ptr as usize)) else {
+        return false;
+    };
+
+    *references -= 1;
+    if *references > 0 {
+        return false;
     }
-}
+    map.remove(&(webcontext_ptr as usize));
+    drop(map);
 
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_event_loop_poll(
-    event_loop_ptr: *mut c_void,
-    callback: extern "C" fn(event: *const c_char, data: *const c_char),
-) {
-    let event_loop: &mut EventLoop<CustomEvent> =
-        unsafe { &mut *(event_loop_ptr as *mut EventLoop<CustomEvent>) };
+    let mut profiles = PROFILE_MAP.lock().unwrap();
+    profiles.retain(|_, webcontext| *webcontext != webcontext_ptr as usize);
+    drop(profiles);
 
-    fn call_callback(
-        callback: extern "C" fn(event: *const c_char, data: *const c_char),
-        event_name: &str,
-        data_json: &serde_json::Value,
-    ) {
-        let event_c = string_to_ptr(event_name);
-        let data_c = string_to_ptr(data_json.to_string().as_str());
+    let mut proxies = WEBCONTEXT_PROXY_MAP.lock().unwrap();
+    proxies.remove(&(webcontext_ptr as usize));
+    drop(proxies);
 
-        callback(event_c.as_ptr(), data_c.as_ptr());
+    let mut languages = WEBCONTEXT_LANGUAGE_MAP.lock().unwrap();
+    languages.remove(&(webcontext_ptr as usize));
+    drop(languages);
+    return true;
+}
+//#endregion
+
+//#region Profiles
+// Every webview using a data directory shares its web context, so schemes, the proxy and the
+// languages set on that context apply to all of them.
+
+// A profile name is a single directory inside `directory`, never a path or a drive prefix.
+fn profile_path(directory: &str, name: &str) -> Option<PathBuf> {
+    let mut components = Path::new(name).components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(_)), None) => Some(Path::new(directory).join(name)),
+        _ => None,
     }
+}
 
-    event_loop.run_return(
-        |event: Event<CustomEvent>,
-         _: &tao::event_loop::EventLoopWindowTarget<CustomEvent>,
-         control_flow: &mut ControlFlow| {
-            *control_flow = ControlFlow::Wait;
+// Open data directories are keyed by their canonical path, so different spellings of the same
+// directory share one context. The directory is created to have a canonical path.
+fn profile_key(path: &Path) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(path)?;
+    return std::fs::canonicalize(path);
+}
 
-            match event {
-                Event::WindowEvent {
-                    window_id,
-                    event: WindowEvent::CloseRequested,
-                    ..
-                } => {
-                    let custom_id = get_custom_window_id(&window_id);
-                    call_callback(
-                        callback,
-                        "window_close_requested",
-                        &json!({ "id": custom_id }),
-                    );
-                }
+fn is_profile_open(path: &Path) -> bool {
+    let Ok(path) = std::fs::canonicalize(path) else {
+        return false;
+    };
+    let profiles = PROFILE_MAP.lock().unwrap();
+    profiles.contains_key(&path)
+}
 
-                Event::WindowEvent {
-                    window_id,
-                    event: WindowEvent::Focused(state),
-                    ..
-                } => {
-                    let custom_id = get_custom_window_id(&window_id);
-                    call_callback(
-                        callback,
-                        "window_focused",
-                        &json!({
-                            "id": custom_id,
-                            "focused": state
-                        }),
-                    );
-                }
+// Contexts are reused for a data directory that is already open, WebKitGTK and WebView2 can't
+// share one between two contexts.
+fn open_webcontext(path: &Path) -> *mut c_void {
+    return open_webcontext_with(path, |path| {
+        webcontext_to_ptr(WebContext::new(Some(path.to_path_buf())))
+    });
+}
 
-                Event::WindowEvent {
-                    window_id,
-                    event: WindowEvent::Moved(position),
-                    ..
-                } => {
-                    let custom_id = get_custom_window_id(&window_id);
+fn open_webcontext_with(path: &Path, create: impl FnOnce(&Path) -> *mut c_void) -> *mut c_void {
+    let key = match profile_key(path) {
+        Ok(key) => key,
+        Err(error) => {
+            set_last_error(format!("Failed to open {}: {error}", path.display()));
+            return std::ptr::null_mut();
+        }
+    };
 
-                    call_callback(
-                        callback,
-                        "window_moved",
-                        &json!({
-                            "id": custom_id,
-                            "x": position.x,
-                            "y": position.y
-                        }),
-                    );
-                }
+    let mut profiles = PROFILE_MAP.lock().unwrap();
+    if let Some(webcontext) = profiles.get(&key) {
+        let webcontext_ptr = *webcontext as *mut c_void;
+        retain_webcontext(webcontext_ptr);
+        return webcontext_ptr;
+    }
 
-                Event::WindowEvent {
-                    window_id,
-                    event: WindowEvent::Resized(size),
-                    ..
-                } => {
-                    let custom_id = get_custom_window_id(&window_id);
-                    call_callback(
-                        callback,
-                        "window_resized",
-                        &json!({
-                            "id": custom_id,
-                            "width": size.width,
-                            "height": size.height
-                        }),
-                    );
-                }
+    let webcontext_ptr = create(path);
+    retain_webcontext(webcontext_ptr);
+    profiles.insert(key, webcontext_ptr as usize);
+    return webcontext_ptr;
+}
+//#endregion
 
-                Event::UserEvent(CustomEvent::TrayIconEvent(tray_event)) => match tray_event {
-                    TrayIconEvent::Click {
-                        button_state: MouseButtonState::Up,
-                        ..
-                    } => {
-                        call_callback(
-                            callback,
-                            "tray_clicked",
-                            &json!({ "id": tray_event.id().0.parse().unwrap_or(0) }),
-                        );
-                    }
-                    _ => (),
-                },
+//#region Protocol responder map management
+fn insert_protocol_responder(webview_id: u16, responder: RequestAsyncResponder) -> u32 {
+    let request_id = PROTOCOL_REQUEST_INDEX.fetch_add(1, Ordering::Relaxed);
+    let mut map = PROTOCOL_RESPONDER_MAP.lock().unwrap();
+    map.insert(request_id, (webview_id, responder));
+    request_id
+}
 
-                Event::MainEventsCleared => {
-                    *control_flow = ControlFlow::Exit;
-                }
-                _ => (),
-            }
-        },
-    );
+fn remove_protocol_responder(request_id: u32) -> Option<RequestAsyncResponder> {
+    let mut map = PROTOCOL_RESPONDER_MAP.lock().unwrap();
+    map.remove(&request_id).map(|(_, responder)| responder)
 }
 
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_window_create(
-    event_loop_ptr: *mut c_void,
-    window_id: u16,
-    options_str_ptr: *mut c_void,
-) -> *mut c_void {
-    let event_loop = event_loop_from_ptr(event_loop_ptr);
-    let options_str = string_from_ptr(options_str_ptr);
-    let options: Value = serde_json::from_str(&options_str).unwrap();
-
-    let mut builder = WindowBuilder::new();
+// Requests of a destroyed webview will never be answered from Bun, they are failed so the
+// platform webview can release them.
+fn fail_protocol_responders(webview_id: u16) {
+    let responders: Vec<RequestAsyncResponder> = {
+        let mut map = PROTOCOL_RESPONDER_MAP.lock().unwrap();
+        let request_ids: Vec<u32> = map
+            .iter()
+            .filter(|(_, (owner, _))| *owner == webview_id)
+            .map(|(request_id, _)| *request_id)
+            .collect();
+        request_ids
+            .iter()
+            .filter_map(|request_id| map.remove(request_id))
+            .map(|(_, responder)| responder)
+            .collect()
+    };
 
-    if options["always_on_bottom"].is_boolean() {
-        builder = builder.with_always_on_bottom(options["always_on_bottom"].as_bool().unwrap());
+    for responder in responders {
+        responder.respond(Response::builder().status(503).body(Vec::new()).unwrap());
     }
+}
+//#endregion
 
-    if options["always_on_top"].is_boolean() {
-        builder = builder.with_always_on_top(options["always_on_top"].as_bool().unwrap());
-    }
+//#region Asset server map management
+fn insert_asset_server(webview_id: u16, protocol: &str, asset_server: AssetServer) {
+    let mut map = ASSET_SERVER_MAP.lock().unwrap();
+    map.insert((webview_id, protocol.to_string()), Arc::new(asset_server));
+}
 
-    if options["closable"].is_boolean() {
-        builder = builder.with_closable(options["closable"].as_bool().unwrap());
-    }
+fn remove_asset_servers(webview_id: u16) {
+    let mut map = ASSET_SERVER_MAP.lock().unwrap();
+    map.retain(|(owner, _), _| *owner != webview_id);
+}
 
-    if options["content_protection"].is_boolean() {
-        builder = builder.with_content_protection(options["content_protection"].as_bool().unwrap());
-    }
+fn get_asset_server(webview_id: u16, protocol: &str) -> Option<Arc<AssetServer>> {
+    let map = ASSET_SERVER_MAP.lock().unwrap();
+    map.get(&(webview_id, protocol.to_string())).cloned()
+}
+//#endregion
 
-    if options["decorations"] == false {
-        builder = builder.with_decorations(false);
-    }
+//#region Protocols
+// WebKitGTK registers schemes on the web context, so a scheme only gets one handler. It serves
+// every webview by the id wry passes along, from that webview's asset server or else its protocol
+// handler in Bun.
+fn with_protocol<'a>(builder: WebViewBuilder<'a>, protocol: &str) -> WebViewBuilder<'a> {
+    let protocol = protocol.to_string();
+    builder.with_asynchronous_custom_protocol(protocol.clone(), move |id, request, responder| {
+        let Some(webview_id) = id
+            .parse::<u16>()
+            .ok()
+            .filter(|webview_id| get_webview_by_id(*webview_id).is_some())
+        else {
+            responder.respond(Response::builder().status(404).body(Vec::new()).unwrap());
+            return;
+        };
+
+        if let Some(asset_server) = get_asset_server(webview_id, &protocol) {
+            std::thread::spawn(move || {
+                responder.respond(asset_server.handle(&request));
+            });
+            return;
+        }
 
-    if options["focusable"].is_boolean() {
-        builder = builder.with_focusable(options["focusable"].as_bool().unwrap());
-    }
+        let mut headers = serde_json::Map::new();
+        for (name, value) in request.headers() {
+            if let Ok(value) = value.to_str() {
+                headers.insert(name.to_string(), json!(value));
+            }
+        }
 
-    if options["focused"].is_boolean() {
-        builder = builder.with_focused(options["focused"].as_bool().unwrap());
-    }
+        send_custom_event(CustomEvent::ProtocolRequest {
+            webview_id,
+            request_id: insert_protocol_responder(webview_id, responder),
+            protocol: protocol.clone(),
+            method: request.method().to_string(),
+            url: request.uri().to_string(),
+            headers: Value::Object(headers),
+            body: BASE64.encode(request.body()),
+        });
+    })
+}
+//#endregion
 
-    if options["size"].is_object() {
-        let width = options["size"]["width"].as_u64().unwrap() as f64;
-        let height = options["size"]["height"].as_u64().unwrap() as f64;
-        builder = builder.with_inner_size(LogicalSize::new(width, height));
-    }
+//#region Downloads
+fn decide_download(webview_id: u16, url: &str, path: &mut PathBuf) -> bool {
+    let suggested_filename = path
+        .file_name()
+        .map(|file_name| file_name.to_string_lossy().to_string());
+    let decision = request_decision(
+        "webview_download_started",
+        &json!({
+            "id": webview_id,
+            "url": url,
+            "suggested_filename": suggested_filename,
+            "path": path.to_string_lossy()
+        }),
+    );
 
-    if options["minimum_size"].is_object() {
-        let width = options["minimum_size"]["width"].as_u64().unwrap() as f64;
-        let height = options["minimum_size"]["height"].as_u64().unwrap() as f64;
-        builder = builder.with_min_inner_size(LogicalSize::new(width, height));
+    if decision["allow"] == false {
+        return false;
     }
 
-    if options["maximum_size"].is_object() {
-        let width = options["maximum_size"]["width"].as_u64().unwrap() as f64;
-        let height = options["maximum_size"]["height"].as_u64().unwrap() as f64;
-        builder = builder.with_max_inner_size(LogicalSize::new(width, height));
+    if let Some(destination) = decision["path"].as_str() {
+        *path = PathBuf::from(destination);
     }
+    return true;
+}
 
-    if options["maximizable"].is_boolean() {
-        builder = builder.with_maximizable(options["maximizable"].as_bool().unwrap());
-    }
+// The id wry stores on the WebKit view, set through `with_id` when the webview is built.
+#[cfg(target_os = "linux")]
+fn webkit_webview_id(webview: &webkit2gtk::WebView) -> Option<u16> {
+    use gtk::glib::prelude::ObjectExt;
 
-    if options["maximized"].is_boolean() {
-        builder = builder.with_maximized(options["maximized"].as_bool().unwrap());
-    }
+    let id = unsafe { webview.data::<String>("webview_id") }?;
+    return unsafe { id.as_ref() }.parse().ok();
+}
 
-    if options["minimizable"].is_boolean() {
-        builder = builder.with_minimizable(options["minimizable"].as_bool().unwrap());
-    }
+#[cfg(target_os = "linux")]
+fn download_destination(url: &str, suggested_filename: &str) -> PathBuf {
+    let directory = gtk::glib::user_special_dir(gtk::glib::UserDirectory::Downloads)
+        .or_else(|| std::env::current_dir().ok())
+        .unwrap_or_default();
 
-    if options["position"].is_object() {
-        let x = options["position"]["x"].as_i64().unwrap() as f64;
-        let y = options["position"]["y"].as_i64().unwrap() as f64;
-        builder = builder.with_position(LogicalPosition::new(x, y));
-    }
+    // WebKitGTK suggests the raw data as the file name for unnamed `data:` downloads.
+    let suggested_filename = if url.starts_with("data:") && url.contains(suggested_filename) {
+        "download"
+    } else {
+        suggested_filename
+    };
+    let (stem, extension) = match suggested_filename.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem, format!(".{extension}")),
+        _ => (suggested_filename, String::new()),
+    };
 
-    if options["resizable"].is_boolean() {
-        builder = builder.with_resizable(options["resizable"].as_bool().unwrap());
+    // Existing files are kept, like WebView2 does.
+    let mut path = directory.join(suggested_filename);
+    let mut counter = 1;
+    while path.exists() {
+        path = directory.join(format!("{stem} ({counter}){extension}"));
+        counter += 1;
     }
+    return path;
+}
 
-    if options["title"].is_string() {
-        let title = options["title"].as_str().unwrap();
-        builder = builder.with_title(title);
+// WebKitGTK reports downloads on the web context. The handlers are connected once per context
+// and route each download by the view that started it.
+#[cfg(target_os = "linux")]
+fn connect_downloads(webview: &WebView) {
+    use gtk::glib::prelude::ObjectExt;
+    use webkit2gtk::{DownloadExt, URIRequestExt, URIResponseExt, WebContextExt, WebViewExt};
+    use wry::WebViewExtUnix;
+
+    let Some(context) = webview.webview().context() else {
+        return;
+    };
+    if unsafe { context.data::<bool>("rod-downloads") }.is_some() {
+        return;
     }
+    unsafe { context.set_data("rod-downloads", true) };
+
+    context.connect_download_started(move |_, download| {
+        let Some(webview_id) = download.web_view().as_ref().and_then(webkit_webview_id) else {
+            return;
+        };
+        let url = |download: &webkit2gtk::Download| {
+            download
+                .request()
+                .and_then(|request| request.uri())
+                .map(|uri| uri.to_string())
+                .unwrap_or_default()
+        };
+
+        download.connect_decide_destination(move |download, suggested_filename| {
+            let url = url(download);
+            let mut path = download_destination(&url, suggested_filename);
+            if decide_download(webview_id, &url, &mut path) {
+                download.set_destination(&path.to_string_lossy());
+            } else {
+                download.cancel();
+            }
+            return true;
+        });
+
+        let last_percent = Cell::new(-1);
+        download.connect_estimated_progress_notify(move |download| {
+            // Progress is reported for every received chunk, only whole percents are forwarded.
+            let progress = download.estimated_progress();
+            let percent = (progress * 100.0) as i32;
+            if last_percent.replace(percent) == percent {
+                return;
+            }
 
-    if options["transparent"].is_boolean() {
-        builder = builder.with_transparent(options["transparent"].as_bool().unwrap());
+            send_custom_event(CustomEvent::WebViewDownloadProgress {
+                webview_id,
+                url: url(download),
+                received_bytes: download.received_data_length(),
+                total_bytes: download
+                    .response()
+                    .map(|response| response.content_length())
+                    .unwrap_or_default(),
+                progress,
+            });
+        });
+
+        // Cancelled and failed downloads emit failed before finished.
+        let failed = Rc::new(Cell::new(false));
+        let failed_download = failed.clone();
+        download.connect_failed(move |_, _| failed_download.set(true));
+        download.connect_finished(move |download| {
+            let success = !failed.get();
+            send_custom_event(CustomEvent::WebViewDownloadCompleted {
+                webview_id,
+                url: url(download),
+                path: success
+                    .then(|| download.destination().map(PathBuf::from))
+                    .flatten(),
+                success,
+            });
+        });
+    });
+}
 
-        #[cfg(target_os = "windows")]
-        {
-            use tao::platform::windows::WindowBuilderExtWindows;
-            builder = builder.with_undecorated_shadow(false);
+// wry doesn't forward the progress of WebView2 downloads, it's followed from a DownloadStarting
+// handler of its own. That handler runs after wry's, which has decided about the download by then.
+#[cfg(target_os = "windows")]
+fn connect_download_progress(webview: &WebView, webview_id: u16) {
+    use webview2_com::Microsoft::Web::WebView2::Win32::ICoreWebView2_4;
+    use webview2_com::{
+        BytesReceivedChangedEventHandler, DownloadStartingEventHandler, take_pwstr,
+    };
+    use windows::core::{Interface, PWSTR};
+    use wry::WebViewExtWindows;
+
+    let handler = DownloadStartingEventHandler::create(Box::new(move |_, args| {
+        let Some(args) = args else {
+            return Ok(());
+        };
+
+        let last_step = Cell::new(-1);
+        let progress_handler =
+            BytesReceivedChangedEventHandler::create(Box::new(move |download, _| {
+                let Some(download) = download else {
+                    return Ok(());
+                };
+                let (mut received_bytes, mut total_bytes) = (0i64, 0i64);
+                unsafe {
+                    download.BytesReceived(&mut received_bytes)?;
+                    download.TotalBytesToReceive(&mut total_bytes)?;
+                }
+
+                // Like on Linux only whole percents are forwarded, or every MiB when the size is
+                // unknown.
+                let (progress, step) = if total_bytes > 0 {
+                    let progress = received_bytes as f64 / total_bytes as f64;
+                    (progress, (progress * 100.0) as i64)
+                } else {
+                    (0.0, received_bytes >> 20)
+                };
+                if last_step.replace(step) == step {
+                    return Ok(());
+                }
+
+                let mut uri = PWSTR::null();
+                unsafe { download.Uri(&mut uri)? };
+                send_custom_event(CustomEvent::WebViewDownloadProgress {
+                    webview_id,
+                    url: take_pwstr(uri),
+                    received_bytes: received_bytes.max(0) as u64,
+                    total_bytes: total_bytes.max(0) as u64,
+                    progress,
+                });
+                Ok(())
+            }));
+
+        unsafe {
+            args.DownloadOperation()?
+                .add_BytesReceivedChanged(&progress_handler, &mut 0i64)
         }
-    }
+    }));
 
-    if options["visible"].is_boolean() {
-        builder = builder.with_visible(options["visible"].as_bool().unwrap());
-    }
+    let _ = unsafe {
+        webview
+            .webview()
+            .cast::<ICoreWebView2_4>()
+            .and_then(|webview| webview.add_DownloadStarting(&handler, &mut 0i64))
+    };
+}
+//#endregion
 
-    if options["visible_on_all_workspaces"].is_boolean() {
-        builder = builder.with_visible_on_all_workspaces(
-            options["visible_on_all_workspaces"].as_bool().unwrap(),
-        );
+//#region Request headers
+fn header_map_from_value(headers: &Value) -> HeaderMap {
+    let mut header_map = HeaderMap::new();
+    let Some(headers) = headers.as_object() else {
+        return header_map;
+    };
+
+    for (name, value) in headers {
+        let Some(value) = value.as_str() else {
+            continue;
+        };
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(value),
+        ) {
+            header_map.insert(name, value);
+        }
     }
 
-    let window = builder.build(event_loop).unwrap();
+    return header_map;
+}
+//#endregion
 
-    // Store mapping from tao WindowId -> user-provided id
-    let tao_id = window.id();
-    {
-        let mut map = WINDOW_ID_MAP.lock().unwrap();
-        map.insert(tao_id, window_id);
-    }
+//#region Cookies
+fn cookie_to_value(cookie: &Cookie) -> Value {
+    let same_site = cookie.same_site().map(|same_site| match same_site {
+        SameSite::Strict => "strict",
+        SameSite::Lax => "lax",
+        SameSite::None => "none",
+    });
 
-    return window_to_ptr(window);
+    return json!({
+        "name": cookie.name(),
+        "value": cookie.value(),
+        "domain": cookie.domain(),
+        "path": cookie.path(),
+        "expires": cookie.expires_datetime().map(|expires| expires.unix_timestamp()),
+        "http_only": cookie.http_only(),
+        "secure": cookie.secure(),
+        "same_site": same_site
+    });
 }
 
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_window_destroy(window_ptr: *mut c_void) {
-    let window = window_from_ptr(window_ptr);
-    remove_window_id(&window.id());
+fn cookie_from_value(value: &Value) -> Option<Cookie<'static>> {
+    let name = value["name"].as_str()?.to_string();
+    let mut cookie = Cookie::new(name, value["value"].as_str().unwrap_or("").to_string());
 
-    unsafe {
-        drop(Box::from_raw(window_ptr as *mut Window));
+    if let Some(domain) = value["domain"].as_str() {
+        cookie.set_domain(domain.to_string());
+    }
+    if let Some(path) = value["path"].as_str() {
+        cookie.set_path(path.to_string());
+    }
+    if let Some(expires) = value["expires"].as_i64()
+        && let Ok(expires) = OffsetDateTime::from_unix_timestamp(expires)
+    {
+        cookie.set_expires(expires);
+    }
+    if let Some(http_only) = value["http_only"].as_bool() {
+        cookie.set_http_only(http_only);
+    }
+    if let Some(secure) = value["secure"].as_bool() {
+        cookie.set_secure(secure);
+    }
+    match value["same_site"].as_str() {
+        Some("strict") => cookie.set_same_site(SameSite::Strict),
+        Some("lax") => cookie.set_same_site(SameSite::Lax),
+        Some("none") => cookie.set_same_site(SameSite::None),
+        _ => {}
     }
-}
 
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_window_get_title(window_ptr: *mut c_void) -> *const c_char {
-    let window = window_from_ptr(window_ptr);
-    return string_to_ptr(window.title().as_str()).into_raw();
+    return Some(cookie);
 }
+//#endregion
 
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_window_get_size(window_ptr: *mut c_void) -> *const c_char {
-    let window = window_from_ptr(window_ptr);
-    let size = window.inner_size();
-    return string_to_ptr(&json!({"width": size.width, "height": size.height}).to_string())
-        .into_raw();
+//#region Language
+// WebView2 derives Accept-Language from the system language when the environment is created, only
+// WebKitGTK can change it.
+fn set_accept_language(webview: &WebView, accept_language: &str) -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        let _ = (webview, accept_language);
+        set_last_error("WebView2 does not support setting the accept language".to_string());
+        return false;
+    }
+    #[cfg(target_os = "linux")]
+    {
+        use webkit2gtk::{WebContextExt, WebViewExt};
+        use wry::WebViewExtUnix;
+        let Some(context) = webview.webview().context() else {
+            set_last_error("The webview has no web context".to_string());
+            return false;
+        };
+
+        // WebKitGTK takes plain language tags and computes the quality values itself.
+        let languages: Vec<&str> = accept_language
+            .split(',')
+            .filter_map(|language| language.split(';').next())
+            .map(str::trim)
+            .filter(|language| !language.is_empty())
+            .collect();
+        context.set_preferred_languages(&languages);
+        return true;
+    }
 }
 
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_window_get_position(window_ptr: *mut c_void) -> *const c_char {
-    let window = window_from_ptr(window_ptr);
-    let position = window.outer_position().unwrap();
-    return string_to_ptr(&json!({"x": position.x, "y": position.y}).to_string()).into_raw();
+fn set_webcontext_language(webcontext_ptr: *mut c_void, accept_language: &str) {
+    let mut map = WEBCONTEXT_LANGUAGE_MAP.lock().unwrap();
+    map.insert(webcontext_ptr as usize, accept_language.to_string());
 }
 
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_window_get_always_on_top(window_ptr: *mut c_void) -> bool {
-    let window = window_from_ptr(window_ptr);
-    return window.is_always_on_top();
+fn get_webcontext_language(webcontext_ptr: *mut c_void) -> Option<String> {
+    let map = WEBCONTEXT_LANGUAGE_MAP.lock().unwrap();
+    map.get(&(webcontext_ptr as usize)).cloned()
 }
+//#endregion
 
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_window_get_closable(window_ptr: *mut c_void) -> bool {
-    let window = window_from_ptr(window_ptr);
-    return window.is_closable();
-}
+//#region Proxy
+fn proxy_config_from_value(proxy: &Value) -> Option<ProxyConfig> {
+    let host = proxy["host"].as_str()?.to_string();
+    let port = proxy["port"].as_u64()?.to_string();
+    let endpoint = ProxyEndpoint { host, port };
 
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_window_get_decorated(window_ptr: *mut c_void) -> bool {
-    let window = window_from_ptr(window_ptr);
-    return window.is_decorated();
+    match proxy["type"].as_str() {
+        Some("socks5") => Some(ProxyConfig::Socks5(endpoint)),
+        Some("http") | None => Some(ProxyConfig::Http(endpoint)),
+        Some(_) => None,
+    }
 }
 
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_window_get_focused(window_ptr: *mut c_void) -> bool {
-    let window = window_from_ptr(window_ptr);
-    return window.is_focused();
+fn proxy_uri(proxy: &ProxyConfig) -> String {
+    match proxy {
+        ProxyConfig::Http(endpoint) => format!("http://{}:{}", endpoint.host, endpoint.port),
+        ProxyConfig::Socks5(endpoint) => format!("socks5://{}:{}", endpoint.host, endpoint.port),
+    }
 }
 
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_window_get_maximizable(window_ptr: *mut c_void) -> bool {
-    let window = window_from_ptr(window_ptr);
-    return window.is_maximizable();
+fn set_webcontext_proxy(webcontext_ptr: *mut c_void, proxy: Option<String>) {
+    let mut map = WEBCONTEXT_PROXY_MAP.lock().unwrap();
+    map.insert(webcontext_ptr as usize, proxy);
 }
 
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_window_get_maximized(window_ptr: *mut c_void) -> bool {
-    let window = window_from_ptr(window_ptr);
-    return window.is_maximized();
+fn get_webcontext_proxy(webcontext_ptr: *mut c_void) -> Option<String> {
+    let map = WEBCONTEXT_PROXY_MAP.lock().unwrap();
+    map.get(&(webcontext_ptr as usize)).cloned().flatten()
 }
+//#endregion
 
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_window_get_minimizable(window_ptr: *mut c_void) -> bool {
-    let window = window_from_ptr(window_ptr);
-    return window.is_minimizable();
-}
+//#region Colors
+// Colors cross the FFI boundary as "#rrggbb" or "#rrggbbaa" strings.
+fn color_from_value(color: &Value) -> Option<wry::RGBA> {
+    let hex = color.as_str()?.strip_prefix('#')?;
+    // from_str_radix also takes a leading sign, every character has to be a hex digit.
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) || (hex.len() != 6 && hex.len() != 8) {
+        return None;
+    }
 
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_window_get_minimized(window_ptr: *mut c_void) -> bool {
-    let window = window_from_ptr(window_ptr);
-    return window.is_minimized();
+    let channel = |index: usize| u8::from_str_radix(&hex[index..index + 2], 16).ok();
+    let alpha = if hex.len() == 8 { channel(6)? } else { 255 };
+    return Some((channel(0)?, channel(2)?, channel(4)?, alpha));
 }
+//#endregion
 
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_window_get_resizable(window_ptr: *mut c_void) -> bool {
-    let window = window_from_ptr(window_ptr);
-    return window.is_resizable();
+//#region Browsing data
+fn finish_clear_data(request_id: u32, success: bool) {
+    send_custom_event(CustomEvent::WebContextDataCleared {
+        request_id,
+        success,
+    });
 }
 
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_window_get_visible(window_ptr: *mut c_void) -> bool {
-    let window = window_from_ptr(window_ptr);
-    return window.is_visible();
+fn unix_now() -> f64 {
+    return std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64();
 }
 
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_window_set_always_on_bottom(
-    window_ptr: *mut c_void,
-    always_on_bottom: bool,
-) {
-    let window = window_from_ptr(window_ptr);
-    window.set_always_on_bottom(always_on_bottom);
+// WebKitGTK clears data modified within a timespan before now, in microseconds. A timespan of zero
+// clears everything, so it's only used when no start is given. A start in the future has nothing
+// to clear.
+#[cfg(target_os = "linux")]
+fn clear_timespan(since_timestamp: f64, now: f64) -> Option<i64> {
+    if since_timestamp == 0.0 {
+        return Some(0);
+    }
+    if since_timestamp > now {
+        return None;
+    }
+    return Some((((now - since_timestamp) * 1_000_000.0) as i64).max(1));
 }
 
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_window_set_always_on_top(
-    window_ptr: *mut c_void,
-    always_on_top: bool,
-) {
-    let window = window_from_ptr(window_ptr);
-    window.set_always_on_top(always_on_top);
+#[cfg(target_os = "linux")]
+fn website_data_types(types: &[String]) -> Result<webkit2gtk::WebsiteDataTypes, String> {
+    use webkit2gtk::WebsiteDataTypes;
+
+    let mut data_types = WebsiteDataTypes::empty();
+    for data_type in types {
+        data_types |= match data_type.as_str() {
+            "cache" => {
+                WebsiteDataTypes::MEMORY_CACHE
+                    | WebsiteDataTypes::DISK_CACHE
+                    | WebsiteDataTypes::OFFLINE_APPLICATION_CACHE
+                    | WebsiteDataTypes::DOM_CACHE
+            }
+            "cookies" => WebsiteDataTypes::COOKIES,
+            "local_storage" => WebsiteDataTypes::LOCAL_STORAGE,
+            "session_storage" => WebsiteDataTypes::SESSION_STORAGE,
+            "indexeddb" => WebsiteDataTypes::INDEXEDDB_DATABASES,
+            "websql" => WebsiteDataTypes::WEBSQL_DATABASES,
+            "service_workers" => WebsiteDataTypes::SERVICE_WORKER_REGISTRATIONS,
+            "hsts" => WebsiteDataTypes::HSTS_CACHE,
+            "all" => WebsiteDataTypes::ALL,
+            _ => return Err(format!("Unknown browsing data type {data_type}")),
+        };
+    }
+    return Ok(data_types);
 }
 
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_window_set_closable(window_ptr: *mut c_void, closable: bool) {
-    let window = window_from_ptr(window_ptr);
-    window.set_closable(closable);
-}
+// WebKitGTK groups website data by registrable domain, so an origin clears its whole site.
+#[cfg(target_os = "linux")]
+fn clear_browsing_data(
+    webview: &WebView,
+    request_id: u32,
+    types: &[String],
+    since_timestamp: f64,
+    origins: Vec<String>,
+) -> bool {
+    use webkit2gtk::gio::Cancellable;
+    use webkit2gtk::glib::TimeSpan;
+    use webkit2gtk::{
+        WebContextExt, WebViewExt, WebsiteDataManagerExt, WebsiteDataManagerExtManual,
+    };
+    use wry::WebViewExtUnix;
 
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_window_set_content_protection(
-    window_ptr: *mut c_void,
-    content_protection: bool,
-) {
-    let window = window_from_ptr(window_ptr);
-    window.set_content_protection(content_protection);
-}
+    let data_types = match website_data_types(types) {
+        Ok(data_types) => data_types,
+        Err(error) => {
+            set_last_error(error);
+            return false;
+        }
+    };
+    let Some(manager) = webview
+        .webview()
+        .context()
+        .and_then(|context| context.website_data_manager())
+    else {
+        set_last_error("The context has no website data manager".to_string());
+        return false;
+    };
 
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_window_set_decorations(window_ptr: *mut c_void, decorations: bool) {
-    let window = window_from_ptr(window_ptr);
-    window.set_decorations(decorations);
-}
+    if origins.is_empty() {
+        let Some(timespan) = clear_timespan(since_timestamp, unix_now()) else {
+            finish_clear_data(request_id, true);
+            return true;
+        };
+
+        manager.clear(
+            data_types,
+            TimeSpan(timespan),
+            None::<&Cancellable>,
+            move |result| finish_clear_data(request_id, result.is_ok()),
+        );
+        return true;
+    }
 
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_window_set_focus(window_ptr: *mut c_void) {
-    let window = window_from_ptr(window_ptr);
-    window.set_focus();
+    let hosts: Vec<String> = origins.iter().map(|origin| origin_host(origin)).collect();
+    let remove_manager = manager.clone();
+    manager.fetch(data_types, None::<&Cancellable>, move |result| {
+        let Ok(website_data) = result else {
+            return finish_clear_data(request_id, false);
+        };
+
+        let matching: Vec<_> = website_data
+            .iter()
+            .filter(|data| {
+                data.name().is_some_and(|name| {
+                    hosts
+                        .iter()
+                        .any(|host| host == name.as_str() || host.ends_with(&format!(".{name}")))
+                })
+            })
+            .collect();
+
+        remove_manager.remove(data_types, &matching, None::<&Cancellable>, move |result| {
+            finish_clear_data(request_id, result.is_ok())
+        });
+    });
+    return true;
+}
+
+#[cfg(target_os = "windows")]
+fn browsing_data_kinds(types: &[String]) -> Result<i32, String> {
+    use webview2_com::Microsoft::Web::WebView2::Win32::*;
+
+    let mut kinds = 0;
+    for data_type in types {
+        kinds |= match data_type.as_str() {
+            "cache" => {
+                COREWEBVIEW2_BROWSING_DATA_KINDS_DISK_CACHE.0
+                    | COREWEBVIEW2_BROWSING_DATA_KINDS_CACHE_STORAGE.0
+            }
+            "cookies" => COREWEBVIEW2_BROWSING_DATA_KINDS_COOKIES.0,
+            "local_storage" => COREWEBVIEW2_BROWSING_DATA_KINDS_LOCAL_STORAGE.0,
+            "indexeddb" => COREWEBVIEW2_BROWSING_DATA_KINDS_INDEXED_DB.0,
+            "websql" => COREWEBVIEW2_BROWSING_DATA_KINDS_WEB_SQL.0,
+            "service_workers" => COREWEBVIEW2_BROWSING_DATA_KINDS_SERVICE_WORKERS.0,
+            "all" => COREWEBVIEW2_BROWSING_DATA_KINDS_ALL_PROFILE.0,
+            "session_storage" | "hsts" => {
+                return Err(format!("WebView2 can't clear {data_type}"));
+            }
+            _ => return Err(format!("Unknown browsing data type {data_type}")),
+        };
+    }
+    return Ok(kinds);
+}
+
+// The storage types of the DevTools protocol's Storage.clearDataForOrigin.
+#[cfg(target_os = "windows")]
+fn origin_storage_types(types: &[String]) -> Result<String, String> {
+    let mut storage_types = Vec::new();
+    for data_type in types {
+        storage_types.push(match data_type.as_str() {
+            "cache" => "cache_storage",
+            "cookies" => "cookies",
+            "local_storage" => "local_storage",
+            "indexeddb" => "indexeddb",
+            "websql" => "websql",
+            "service_workers" => "service_workers",
+            "all" => "all",
+            "session_storage" | "hsts" => {
+                return Err(format!("WebView2 can't clear {data_type} for an origin"));
+            }
+            _ => return Err(format!("Unknown browsing data type {data_type}")),
+        });
+    }
+    return Ok(storage_types.join(","));
+}
+
+// WebView2 can only clear a time range for the whole profile, origins go through the DevTools
+// protocol which has no time range.
+#[cfg(target_os = "windows")]
+fn clear_browsing_data(
+    webview: &WebView,
+    request_id: u32,
+    types: &[String],
+    since_timestamp: f64,
+    origins: Vec<String>,
+) -> bool {
+    use std::sync::atomic::{AtomicBool, AtomicUsize};
+    use webview2_com::Microsoft::Web::WebView2::Win32::*;
+    use webview2_com::{
+        CallDevToolsProtocolMethodCompletedHandler, ClearBrowsingDataCompletedHandler,
+    };
+    use windows::core::{HSTRING, Interface};
+    use wry::WebViewExtWindows;
+
+    let core_webview = webview.webview();
+
+    if origins.is_empty() {
+        let kinds = match browsing_data_kinds(types) {
+            Ok(kinds) => kinds,
+            Err(error) => {
+                set_last_error(error);
+                return false;
+            }
+        };
+
+        // A start in the future has nothing to clear.
+        let now = unix_now();
+        if since_timestamp > now {
+            finish_clear_data(request_id, true);
+            return true;
+        }
+
+        let clear = || -> windows::core::Result<()> {
+            unsafe {
+                core_webview
+                    .cast::<ICoreWebView2_13>()?
+                    .Profile()?
+                    .cast::<ICoreWebView2Profile2>()?
+                    .ClearBrowsingDataInTimeRange(
+                        COREWEBVIEW2_BROWSING_DATA_KINDS(kinds),
+                        since_timestamp,
+                        now,
+                        &ClearBrowsingDataCompletedHandler::create(Box::new(move |result| {
+                            finish_clear_data(request_id, result.is_ok());
+                            Ok(())
+                        })),
+                    )
+            }
+        };
+        if clear().is_err() {
+            finish_clear_data(request_id, false);
+        }
+        return true;
+    }
+
+    let storage_types = match origin_storage_types(types) {
+        Ok(storage_types) => storage_types,
+        Err(error) => {
+            set_last_error(error);
+            return false;
+        }
+    };
+
+    let remaining = Arc::new(AtomicUsize::new(origins.len()));
+    let success = Arc::new(AtomicBool::new(true));
+    for origin in origins {
+        let (remaining, success) = (remaining.clone(), success.clone());
+        let handler =
+            CallDevToolsProtocolMethodCompletedHandler::create(Box::new(move |result, _| {
+                if result.is_err() {
+                    success.store(false, Ordering::Relaxed);
+                }
+                if remaining.fetch_sub(1, Ordering::Relaxed) == 1 {
+                    finish_clear_data(request_id, success.load(Ordering::Relaxed));
+                }
+                Ok(())
+            }));
+
+        let params = json!({ "origin": origin, "storageTypes": storage_types });
+        let called = unsafe {
+            core_webview.CallDevToolsProtocolMethod(
+                &HSTRING::from("Storage.clearDataForOrigin"),
+                &HSTRING::from(params.to_string()),
+                &handler,
+            )
+        };
+        if called.is_err() {
+            success.store(false, Ordering::Relaxed);
+            if remaining.fetch_sub(1, Ordering::Relaxed) == 1 {
+                finish_clear_data(request_id, false);
+            }
+        }
+    }
+    return true;
+}
+
+#[cfg(target_os = "linux")]
+fn origin_host(origin: &str) -> String {
+    let without_scheme = origin.split_once("://").map_or(origin, |(_, rest)| rest);
+    let authority = without_scheme.split('/').next().unwrap_or_default();
+    let host = authority
+        .rsplit_once(':')
+        .map_or(authority, |(host, _)| host);
+    return host.to_lowercase();
+}
+//#endregion
+
+//#region History
+fn history_state(webview: &WebView) -> (bool, bool) {
+    #[cfg(target_os = "windows")]
+    {
+        use windows::core::BOOL;
+        use wry::WebViewExtWindows;
+        let core_webview = webview.webview();
+        let mut can_go_back = BOOL::default();
+        let mut can_go_forward = BOOL::default();
+        unsafe {
+            let _ = core_webview.CanGoBack(&mut can_go_back);
+            let _ = core_webview.CanGoForward(&mut can_go_forward);
+        }
+        return (can_go_back.as_bool(), can_go_forward.as_bool());
+    }
+    #[cfg(target_os = "linux")]
+    {
+        use webkit2gtk::WebViewExt;
+        use wry::WebViewExtUnix;
+        let inner = webview.webview();
+        return (inner.can_go_back(), inner.can_go_forward());
+    }
+}
+//#endregion
+
+//#region Bounds
+// Bounds cross the FFI boundary in logical pixels as {x, y, width, height}.
+fn rect_from_value(bounds: &Value) -> Option<wry::Rect> {
+    let x = bounds["x"].as_f64()?;
+    let y = bounds["y"].as_f64()?;
+    let width = bounds["width"].as_f64()?;
+    let height = bounds["height"].as_f64()?;
+    return Some(wry::Rect {
+        position: LogicalPosition::new(x, y).into(),
+        size: LogicalSize::new(width, height).into(),
+    });
+}
+
+fn rect_to_value(x: f64, y: f64, width: f64, height: f64) -> Value {
+    return json!({ "x": x, "y": y, "width": width, "height": height });
+}
+
+// Webviews without bounds are packed into the window's default vbox, like wry does. Webviews with
+// bounds go into a gtk::Layout packed in its place, so they can overlap and be restacked. Unlike a
+// gtk::Fixed, a Layout's size doesn't grow with its children. Once a window has a Layout the
+// webviews without bounds move into it and are allocated its whole size, so the vbox isn't split.
+#[cfg(target_os = "linux")]
+fn find_layout(vbox: &gtk::Box) -> Option<gtk::Layout> {
+    use gtk::prelude::*;
+    return vbox
+        .children()
+        .into_iter()
+        .find_map(|child| child.downcast::<gtk::Layout>().ok());
+}
+
+#[cfg(target_os = "linux")]
+fn vbox_layout(vbox: &gtk::Box) -> gtk::Layout {
+    use gtk::glib::prelude::ObjectExt;
+    use gtk::prelude::*;
+    if let Some(layout) = find_layout(vbox) {
+        return layout;
+    }
+
+    let layout = gtk::Layout::new(None::<&gtk::Adjustment>, None::<&gtk::Adjustment>);
+    layout.connect_size_allocate(|layout, allocation| {
+        let fill = gtk::Allocation::new(0, 0, allocation.width(), allocation.height());
+        for child in layout.children() {
+            if unsafe { child.data::<bool>("rod-fill") }.is_some() {
+                child.size_allocate(&fill);
+            }
+        }
+    });
+
+    for child in vbox.children() {
+        if !child.is::<webkit2gtk::WebView>() {
+            continue;
+        }
+        vbox.remove(&child);
+        unsafe { child.set_data("rod-fill", true) };
+        layout.put(&child, 0, 0);
+    }
+    vbox.pack_start(&layout, true, true, 0);
+    layout.show();
+    return layout;
+}
+
+#[cfg(target_os = "linux")]
+fn layout_parent(webview: &WebView) -> Option<gtk::Layout> {
+    use gtk::prelude::*;
+    use wry::WebViewExtUnix;
+    return webview
+        .webview()
+        .parent()
+        .and_then(|parent| parent.downcast::<gtk::Layout>().ok());
+}
+
+// Webviews outside a Layout always fill their window.
+#[cfg(target_os = "linux")]
+fn is_filling(webview: &WebView) -> bool {
+    use gtk::glib::prelude::ObjectExt;
+    use wry::WebViewExtUnix;
+    return layout_parent(webview).is_none()
+        || unsafe { webview.webview().data::<bool>("rod-fill") }.is_some();
+}
+
+// Makes a webview in a Layout cover its whole window, until it's given bounds.
+#[cfg(target_os = "linux")]
+fn fill_window(webview: &WebView) {
+    use gtk::glib::prelude::ObjectExt;
+    use gtk::prelude::*;
+    use wry::WebViewExtUnix;
+    let Some(layout) = layout_parent(webview) else {
+        return;
+    };
+
+    let inner = webview.webview();
+    unsafe { inner.set_data("rod-fill", true) };
+    layout.move_(&inner, 0, 0);
+    inner.set_size_request(-1, -1);
+    layout.queue_resize();
+}
+
+// Children of a Layout are drawn in the order they were put, putting one back moves it to the end
+// of that list. Sending one to the back puts every other child back after it.
+#[cfg(target_os = "linux")]
+fn restack_child(layout: &gtk::Layout, child: &gtk::Widget, to_front: bool) {
+    use gtk::prelude::*;
+    let children = if to_front {
+        vec![child.clone()]
+    } else {
+        layout
+            .children()
+            .into_iter()
+            .filter(|other| other != child)
+            .collect()
+    };
+
+    for child in children {
+        let x = layout.child_property::<i32>(&child, "x");
+        let y = layout.child_property::<i32>(&child, "y");
+        layout.remove(&child);
+        layout.put(&child, x, y);
+    }
+}
+
+fn set_bounds(webview: &WebView, bounds: wry::Rect) -> bool {
+    // A webview filling the vbox moves into the window's Layout to be positioned.
+    #[cfg(target_os = "linux")]
+    if layout_parent(webview).is_none() {
+        use gtk::prelude::*;
+        use wry::WebViewExtUnix;
+        if let Some(vbox) = webview
+            .webview()
+            .parent()
+            .and_then(|parent| parent.downcast::<gtk::Box>().ok())
+        {
+            vbox_layout(&vbox);
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    if let Some(layout) = layout_parent(webview) {
+        use gtk::glib::prelude::ObjectExt;
+        use gtk::prelude::*;
+        use wry::WebViewExtUnix;
+        // wry only positions webviews inside a gtk::Fixed, the Layout is told directly.
+        let inner = webview.webview();
+        let scale_factor = inner.scale_factor() as f64;
+        let (x, y): (i32, i32) = bounds.position.to_logical::<i32>(scale_factor).into();
+        let (width, height): (i32, i32) = bounds.size.to_logical::<i32>(scale_factor).into();
+        unsafe { inner.steal_data::<bool>("rod-fill") };
+        layout.move_(&inner, x, y);
+        inner.set_size_request(width, height);
+        return true;
+    }
+
+    return webview.set_bounds(bounds).is_ok();
+}
+
+fn get_bounds(webview: &WebView) -> Value {
+    #[cfg(target_os = "windows")]
+    {
+        use windows::Win32::UI::HiDpi::GetDpiForWindow;
+        use wry::WebViewExtWindows;
+        let Ok(bounds) = webview.bounds() else {
+            return Value::Null;
+        };
+
+        let mut hwnd = windows::Win32::Foundation::HWND::default();
+        let dpi = unsafe {
+            match webview.controller().ParentWindow(&mut hwnd) {
+                Ok(_) => GetDpiForWindow(hwnd),
+                Err(_) => 96,
+            }
+        };
+        let scale_factor = if dpi == 0 { 1.0 } else { dpi as f64 / 96.0 };
+        let position = bounds.position.to_logical::<f64>(scale_factor);
+        let size = bounds.size.to_logical::<f64>(scale_factor);
+        return rect_to_value(position.x, position.y, size.width, size.height);
+    }
+    #[cfg(target_os = "linux")]
+    {
+        use gtk::prelude::*;
+        use wry::WebViewExtUnix;
+        let inner = webview.webview();
+        let layout = layout_parent(webview);
+        let Some(layout) = layout.filter(|_| !is_filling(webview)) else {
+            let allocation = inner.allocation();
+            return rect_to_value(
+                0.0,
+                0.0,
+                allocation.width() as f64,
+                allocation.height() as f64,
+            );
+        };
+
+        let x = layout.child_property::<i32>(&inner, "x");
+        let y = layout.child_property::<i32>(&inner, "y");
+        let (width, height) = inner.size_request();
+        return rect_to_value(x as f64, y as f64, width as f64, height as f64);
+    }
+}
+
+// Raises or lowers a webview among the other webviews of its window.
+fn restack_webview(webview: &WebView, to_front: bool) -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        use windows::Win32::UI::WindowsAndMessaging::{
+            HWND_BOTTOM, HWND_TOP, SWP_NOACTIVATE, SWP_NOMOVE, SWP_NOSIZE, SetWindowPos,
+        };
+        use wry::WebViewExtWindows;
+        // The controller's parent window is wry's container, restacking it restacks the webview.
+        let mut hwnd = windows::Win32::Foundation::HWND::default();
+        if unsafe { webview.controller().ParentWindow(&mut hwnd) }.is_err() {
+            return false;
+        }
+
+        let insert_after = if to_front { HWND_TOP } else { HWND_BOTTOM };
+        return unsafe {
+            SetWindowPos(
+                hwnd,
+                Some(insert_after),
+                0,
+                0,
+                0,
+                0,
+                SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE,
+            )
+        }
+        .is_ok();
+    }
+    #[cfg(target_os = "linux")]
+    {
+        use gtk::prelude::*;
+        use wry::WebViewExtUnix;
+        let Some(layout) = layout_parent(webview) else {
+            return false;
+        };
+
+        restack_child(&layout, webview.webview().upcast_ref(), to_front);
+        return true;
+    }
+}
+//#endregion
+
+//#region Snapshots
+// The PNG is written to the path when one is given, otherwise it is sent back with the event.
+fn finish_snapshot(
+    webview_id: u16,
+    request_id: u32,
+    path: Option<String>,
+    png: Result<Vec<u8>, String>,
+) {
+    let result = png.and_then(|png| match &path {
+        Some(path) => std::fs::write(path, png)
+            .map(|_| None)
+            .map_err(|error| error.to_string()),
+        None => Ok(Some(png)),
+    });
+
+    send_custom_event(CustomEvent::WebViewSnapshot {
+        webview_id,
+        request_id,
+        path,
+        result,
+    });
+}
+
+#[cfg(target_os = "linux")]
+fn capture_snapshot(
+    webview: &WebView,
+    full_document: bool,
+    finish: impl FnOnce(Result<Vec<u8>, String>) + 'static,
+) {
+    use webkit2gtk::gio::Cancellable;
+    use webkit2gtk::{SnapshotOptions, SnapshotRegion, WebViewExt};
+    use wry::WebViewExtUnix;
+    let region = if full_document {
+        SnapshotRegion::FullDocument
+    } else {
+        SnapshotRegion::Visible
+    };
+
+    webview.webview().snapshot(
+        region,
+        SnapshotOptions::NONE,
+        None::<&Cancellable>,
+        move |result| {
+            finish(
+                result
+                    .map_err(|error| error.to_string())
+                    .and_then(|surface| surface_to_png(&surface)),
+            )
+        },
+    );
+}
+
+// The cairo bindings are built without PNG support, so the pixels are encoded with the image
+// crate. Cairo stores premultiplied native endian ARGB.
+#[cfg(target_os = "linux")]
+fn surface_to_png(surface: &gtk::cairo::Surface) -> Result<Vec<u8>, String> {
+    let image = surface
+        .map_to_image(None)
+        .map_err(|error| error.to_string())?;
+    let width = image.width() as usize;
+    let height = image.height() as usize;
+    let stride = image.stride() as usize;
+    let opaque = image.format() == gtk::cairo::Format::Rgb24;
+
+    let mut rgba = Vec::with_capacity(width * height * 4);
+    image
+        .with_data(|data| {
+            for row in data.chunks(stride).take(height) {
+                for pixel in row[..width * 4].chunks_exact(4) {
+                    let argb = u32::from_ne_bytes([pixel[0], pixel[1], pixel[2], pixel[3]]);
+                    let alpha = if opaque { 255 } else { (argb >> 24) as u8 };
+                    let channel = |shift: u32| {
+                        let value = (argb >> shift) & 0xff;
+                        if alpha == 0 {
+                            return 0;
+                        }
+                        return (value * 255 / alpha as u32).min(255) as u8;
+                    };
+                    rgba.extend_from_slice(&[channel(16), channel(8), channel(0), alpha]);
+                }
+            }
+        })
+        .map_err(|error| error.to_string())?;
+
+    let Some(buffer) = image::RgbaImage::from_raw(width as u32, height as u32, rgba) else {
+        return Err("Invalid snapshot size".to_string());
+    };
+    let mut png = std::io::Cursor::new(Vec::new());
+    buffer
+        .write_to(&mut png, image::ImageFormat::Png)
+        .map_err(|error| error.to_string())?;
+    return Ok(png.into_inner());
+}
+
+#[cfg(target_os = "windows")]
+fn call_devtools_method(
+    core_webview: &webview2_com::Microsoft::Web::WebView2::Win32::ICoreWebView2,
+    method: &str,
+    params: &Value,
+    callback: impl FnOnce(Result<Value, String>) + 'static,
+) {
+    use webview2_com::CallDevToolsProtocolMethodCompletedHandler;
+    use windows::core::HSTRING;
+    // Shared with the error path, the handler never runs when the call itself fails.
+    let callback = Rc::new(Cell::new(Some(callback)));
+    let handler_callback = callback.clone();
+    let handler =
+        CallDevToolsProtocolMethodCompletedHandler::create(Box::new(move |result, json| {
+            if let Some(callback) = handler_callback.take() {
+                callback(
+                    result.map_err(|error| error.message()).and_then(|_| {
+                        serde_json::from_str(&json).map_err(|error| error.to_string())
+                    }),
+                );
+            }
+            Ok(())
+        }));
+
+    let called = unsafe {
+        core_webview.CallDevToolsProtocolMethod(
+            &HSTRING::from(method),
+            &HSTRING::from(params.to_string()),
+            &handler,
+        )
+    };
+    if let Err(error) = called
+        && let Some(callback) = callback.take()
+    {
+        callback(Err(error.message()));
+    }
+}
+
+// WebView2 only captures the visible area natively, both regions go through the DevTools
+// protocol. The full document is captured by clipping to the content size.
+#[cfg(target_os = "windows")]
+fn capture_snapshot(
+    webview: &WebView,
+    full_document: bool,
+    finish: impl FnOnce(Result<Vec<u8>, String>) + 'static,
+) {
+    use wry::WebViewExtWindows;
+    let core_webview = webview.webview();
+    let decode = |result: Result<Value, String>| -> Result<Vec<u8>, String> {
+        let data = result?["data"].as_str().unwrap_or_default().to_string();
+        return BASE64.decode(data).map_err(|error| error.to_string());
+    };
+
+    if !full_document {
+        let params = json!({ "format": "png" });
+        call_devtools_method(
+            &core_webview,
+            "Page.captureScreenshot",
+            &params,
+            move |result| finish(decode(result)),
+        );
+        return;
+    }
+
+    let capture_webview = core_webview.clone();
+    call_devtools_method(
+        &core_webview,
+        "Page.getLayoutMetrics",
+        &json!({}),
+        move |result| {
+            let metrics = match result {
+                Ok(metrics) => metrics,
+                Err(error) => return finish(Err(error)),
+            };
+
+            let size = &metrics["cssContentSize"];
+            let params = json!({
+                "format": "png",
+                "captureBeyondViewport": true,
+                "clip": {
+                    "x": 0,
+                    "y": 0,
+                    "width": size["width"],
+                    "height": size["height"],
+                    "scale": 1
+                }
+            });
+            call_devtools_method(
+                &capture_webview,
+                "Page.captureScreenshot",
+                &params,
+                move |result| finish(decode(result)),
+            );
+        },
+    );
+}
+//#endregion
+
+//#region Printing
+// Page sizes and margins cross the FFI boundary in millimeters.
+struct PdfPage {
+    width: f64,
+    height: f64,
+    margins: [f64; 4],
+    landscape: bool,
+}
+
+fn pdf_page_from_value(options: &Value) -> PdfPage {
+    let (width, height) = match &options["page_size"] {
+        Value::String(name) => match name.as_str() {
+            "a3" => (297.0, 420.0),
+            "a5" => (148.0, 210.0),
+            "letter" => (215.9, 279.4),
+            "legal" => (215.9, 355.6),
+            _ => (210.0, 297.0),
+        },
+        size => (
+            size["width"].as_f64().unwrap_or(210.0),
+            size["height"].as_f64().unwrap_or(297.0),
+        ),
+    };
+
+    let margin = |side: &str| options["margins"][side].as_f64().unwrap_or(10.0);
+    return PdfPage {
+        width,
+        height,
+        margins: [
+            margin("top"),
+            margin("right"),
+            margin("bottom"),
+            margin("left"),
+        ],
+        landscape: options["landscape"] == true,
+    };
+}
+
+// The file backend's printer is named "Print to File" in the user's language, so it's found by
+// its backend instead. The gtk bindings leave out the unix print API.
+#[cfg(target_os = "linux")]
+fn file_printer_name() -> Option<String> {
+    use gtk::glib::translate::FromGlibPtrNone;
+    use gtk::glib::{Object, ffi, gobject_ffi, prelude::*};
+
+    type PrinterFunc =
+        unsafe extern "C" fn(*mut gobject_ffi::GObject, ffi::gpointer) -> ffi::gboolean;
+
+    unsafe extern "C" {
+        fn gtk_enumerate_printers(
+            func: PrinterFunc,
+            data: ffi::gpointer,
+            destroy: ffi::GDestroyNotify,
+            wait: ffi::gboolean,
+        );
+    }
+
+    unsafe extern "C" fn find_file_printer(
+        printer: *mut gobject_ffi::GObject,
+        data: ffi::gpointer,
+    ) -> ffi::gboolean {
+        let printer: Object = unsafe { Object::from_glib_none(printer) };
+        let backend = printer.property::<Option<Object>>("backend");
+        if backend.is_none_or(|backend| backend.type_().name() != "GtkPrintBackendFile") {
+            return ffi::GFALSE;
+        }
+
+        let name = unsafe { &mut *(data as *mut Option<String>) };
+        *name = Some(printer.property::<String>("name"));
+        return ffi::GTRUE;
+    }
+
+    // Waiting keeps the name on the stack alive until every backend has been asked.
+    let mut name: Option<String> = None;
+    unsafe {
+        gtk_enumerate_printers(
+            find_file_printer,
+            &mut name as *mut Option<String> as ffi::gpointer,
+            None,
+            ffi::GTRUE,
+        );
+    }
+    return name;
+}
+
+#[cfg(target_os = "linux")]
+fn print_to_pdf(
+    webview: &WebView,
+    path: &str,
+    page: PdfPage,
+    finish: impl Fn(Option<String>) + 'static,
+) {
+    use gtk::{PageOrientation, PageSetup, PaperSize, PrintSettings, Unit};
+    use webkit2gtk::{PrintOperation, PrintOperationExt};
+    use wry::WebViewExtUnix;
+    let uri = match gtk::glib::filename_to_uri(path, None) {
+        Ok(uri) => uri,
+        Err(error) => return finish(Some(error.to_string())),
+    };
+
+    let orientation = if page.landscape {
+        PageOrientation::Landscape
+    } else {
+        PageOrientation::Portrait
+    };
+    let paper_size = PaperSize::new_custom("rod", "rod", page.width, page.height, Unit::Mm);
+
+    let page_setup = PageSetup::new();
+    page_setup.set_paper_size(&paper_size);
+    page_setup.set_orientation(orientation);
+    page_setup.set_top_margin(page.margins[0], Unit::Mm);
+    page_setup.set_right_margin(page.margins[1], Unit::Mm);
+    page_setup.set_bottom_margin(page.margins[2], Unit::Mm);
+    page_setup.set_left_margin(page.margins[3], Unit::Mm);
+
+    // The file printer writes the PDF without showing any UI.
+    let Some(printer) = file_printer_name() else {
+        return finish(Some("No print to file printer is available".to_string()));
+    };
+    let print_settings = PrintSettings::new();
+    print_settings.set_printer(&printer);
+    print_settings.set(gtk::PRINT_SETTINGS_OUTPUT_FILE_FORMAT, Some("pdf"));
+    print_settings.set(gtk::PRINT_SETTINGS_OUTPUT_URI, Some(&uri));
+    print_settings.set_paper_size(&paper_size);
+    print_settings.set_orientation(orientation);
+
+    let operation = PrintOperation::new(&webview.webview());
+    operation.set_page_setup(&page_setup);
+    operation.set_print_settings(&print_settings);
+
+    // failed is emitted before finished, which reports the outcome once.
+    let error = Rc::new(Cell::new(None));
+    let failed_error = error.clone();
+    operation.connect_failed(move |_, failure| failed_error.set(Some(failure.to_string())));
+    operation.connect_finished(move |_| finish(error.take()));
+    operation.print();
+}
+
+#[cfg(target_os = "windows")]
+fn print_to_pdf(
+    webview: &WebView,
+    path: &str,
+    page: PdfPage,
+    finish: impl Fn(Option<String>) + 'static,
+) {
+    use webview2_com::Microsoft::Web::WebView2::Win32::*;
+    use webview2_com::PrintToPdfCompletedHandler;
+    use windows::core::{HSTRING, Interface};
+    use wry::WebViewExtWindows;
+    // WebView2 print settings are in inches.
+    let inches = |millimeters: f64| millimeters / 25.4;
+    let finish = Rc::new(finish);
+    let handler_finish = finish.clone();
+    let handler = PrintToPdfCompletedHandler::create(Box::new(move |result, success| {
+        let success: bool = success.into();
+        handler_finish(match result {
+            Ok(_) if success => None,
+            Ok(_) => Some("Printing to PDF failed".to_string()),
+            Err(error) => Some(error.message()),
+        });
+        Ok(())
+    }));
+
+    let printed = unsafe {
+        (|| {
+            let environment = webview.environment().cast::<ICoreWebView2Environment6>()?;
+            let settings = environment.CreatePrintSettings()?;
+            settings.SetPageWidth(inches(page.width))?;
+            settings.SetPageHeight(inches(page.height))?;
+            settings.SetMarginTop(inches(page.margins[0]))?;
+            settings.SetMarginRight(inches(page.margins[1]))?;
+            settings.SetMarginBottom(inches(page.margins[2]))?;
+            settings.SetMarginLeft(inches(page.margins[3]))?;
+            settings.SetShouldPrintBackgrounds(true)?;
+            if page.landscape {
+                settings.SetOrientation(COREWEBVIEW2_PRINT_ORIENTATION_LANDSCAPE)?;
+            }
+
+            webview.webview().cast::<ICoreWebView2_7>()?.PrintToPdf(
+                &HSTRING::from(path),
+                &settings,
+                &handler,
+            )
+        })()
+    };
+    if let Err(error) = printed {
+        finish(Some(error.message()));
+    }
+}
+//#endregion
+
+//#region icons
+fn load_tray_icon(path: &std::path::Path) -> tray_icon::Icon {
+    let (icon_rgba, icon_width, icon_height) = {
+        let image = image::open(path)
+            .expect("Failed to open icon path")
+            .into_rgba8();
+        let (width, height) = image.dimensions();
+        let rgba = image.into_raw();
+        (rgba, width, height)
+    };
+    tray_icon::Icon::from_rgba(icon_rgba, icon_width, icon_height).expect("Failed to open icon")
+}
+//#endregion
+
+//#region Pointer conversions
+fn string_from_ptr(string_ptr: *mut c_void) -> String {
+    unsafe { CStr::from_ptr(string_ptr as *const c_char) }
+        .to_str()
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn string_to_ptr(string: &str) -> CString {
+    return CString::new(string).unwrap();
+}
+
+fn event_loop_from_ptr(event_loop_ptr: *mut c_void) -> &'static mut EventLoop<CustomEvent> {
+    unsafe { &mut *(event_loop_ptr as *mut EventLoop<CustomEvent>) }
+}
+
+fn event_loop_to_ptr(event_loop: EventLoop<CustomEvent>) -> *mut c_void {
+    Box::into_raw(Box::new(event_loop)) as *mut c_void
+}
+
+fn window_from_ptr(window_ptr: *mut c_void) -> &'static mut Window {
+    unsafe { &mut *(window_ptr as *mut Window) }
+}
+
+fn window_to_ptr(window: Window) -> *mut c_void {
+    Box::into_raw(Box::new(window)) as *mut c_void
+}
+
+fn webcontext_from_ptr(webcontext_ptr: *mut c_void) -> &'static mut WebContext {
+    unsafe { &mut *(webcontext_ptr as *mut WebContext) }
+}
+
+fn webcontext_to_ptr(webcontext: WebContext) -> *mut c_void {
+    Box::into_raw(Box::new(webcontext)) as *mut c_void
+}
+
+fn webview_from_ptr(webview_ptr: *mut c_void) -> &'static mut WebView {
+    unsafe { &mut *(webview_ptr as *mut WebView) }
+}
+
+fn webview_to_ptr(webview: WebView) -> *mut c_void {
+    Box::into_raw(Box::new(webview)) as *mut c_void
+}
+
+// fn tray_from_ptr(tray_ptr: *mut c_void) -> &'static mut TrayIcon {
+//     unsafe { &mut *(tray_ptr as *mut TrayIcon) }
+// }
+
+fn tray_to_ptr(tray: TrayIcon) -> *mut c_void {
+    Box::into_raw(Box::new(tray)) as *mut c_void
+}
+//#endregion
+
+#[unsafe(no_mangle)]
+pub extern "C" fn rod_event_loop_create() -> *mut c_void {
+    init_runtime_env();
+    let event_loop = EventLoopBuilder::<CustomEvent>::with_user_event().build();
+
+    set_event_loop_proxy(event_loop.create_proxy());
+
+    let proxy = event_loop.create_proxy();
+    TrayIconEvent::set_event_handler(Some(move |event| {
+        proxy.send_event(CustomEvent::TrayIconEvent(event)).ok();
+    }));
+
+    return event_loop_to_ptr(event_loop);
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_event_loop_destroy(event_loop_ptr: *mut c_void) {
+    unsafe {
+        drop(Box::from_raw(event_loop_ptr as *mut EventLoop<()>));
+    }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_event_loop_poll(
+    event_loop_ptr: *mut c_void,
+    callback: extern "C" fn(event: *const c_char, data: *const c_char),
+) {
+    let event_loop: &mut EventLoop<CustomEvent> =
+        unsafe { &mut *(event_loop_ptr as *mut EventLoop<CustomEvent>) };
+
+    fn call_callback(
+        callback: extern "C" fn(event: *const c_char, data: *const c_char),
+        event_name: &str,
+        data_json: &serde_json::Value,
+    ) {
+        let event_c = string_to_ptr(event_name);
+        let data_c = string_to_ptr(data_json.to_string().as_str());
+
+        callback(event_c.as_ptr(), data_c.as_ptr());
+    }
+
+    event_loop.run_return(
+        |event: Event<CustomEvent>,
+         _: &tao::event_loop::EventLoopWindowTarget<CustomEvent>,
+         control_flow: &mut ControlFlow| {
+            *control_flow = ControlFlow::Wait;
+
+            match event {
+                Event::WindowEvent {
+                    window_id,
+                    event: WindowEvent::CloseRequested,
+                    ..
+                } => {
+                    let custom_id = get_custom_window_id(&window_id);
+                    call_callback(
+                        callback,
+                        "window_close_requested",
+                        &json!({ "id": custom_id }),
+                    );
+                }
+
+                Event::WindowEvent {
+                    window_id,
+                    event: WindowEvent::Focused(state),
+                    ..
+                } => {
+                    let custom_id = get_custom_window_id(&window_id);
+                    call_callback(
+                        callback,
+                        "window_focused",
+                        &json!({
+                            "id": custom_id,
+                            "focused": state
+                        }),
+                    );
+                }
+
+                Event::WindowEvent {
+                    window_id,
+                    event: WindowEvent::Moved(position),
+                    ..
+                } => {
+                    let custom_id = get_custom_window_id(&window_id);
+
+                    call_callback(
+                        callback,
+                        "window_moved",
+                        &json!({
+                            "id": custom_id,
+                            "x": position.x,
+                            "y": position.y
+                        }),
+                    );
+                }
+
+                Event::WindowEvent {
+                    window_id,
+                    event: WindowEvent::Resized(size),
+                    ..
+                } => {
+                    let custom_id = get_custom_window_id(&window_id);
+                    call_callback(
+                        callback,
+                        "window_resized",
+                        &json!({
+                            "id": custom_id,
+                            "width": size.width,
+                            "height": size.height
+                        }),
+                    );
+                }
+
+                Event::UserEvent(CustomEvent::TrayIconEvent(tray_event)) => match tray_event {
+                    TrayIconEvent::Click {
+                        button_state: MouseButtonState::Up,
+                        ..
+                    } => {
+                        call_callback(
+                            callback,
+                            "tray_clicked",
+                            &json!({ "id": tray_event.id().0.parse().unwrap_or(0) }),
+                        );
+                    }
+                    _ => (),
+                },
+
+                Event::UserEvent(CustomEvent::WebViewIpcMessage {
+                    webview_id,
+                    url,
+                    message,
+                }) => {
+                    call_callback(
+                        callback,
+                        "webview_ipc_message",
+                        &json!({
+                            "id": webview_id,
+                            "url": url,
+                            "message": message
+                        }),
+                    );
+                }
+
+                Event::UserEvent(CustomEvent::WebViewInvoke {
+                    webview_id,
+                    call_id,
+                    command,
+                    args,
+                }) => {
+                    call_callback(
+                        callback,
+                        "webview_invoke",
+                        &json!({
+                            "id": webview_id,
+                            "call_id": call_id,
+                            "command": command,
+                            "args": args
+                        }),
+                    );
+                }
+
+                Event::UserEvent(CustomEvent::WebViewEvalResult {
+                    webview_id,
+                    request_id,
+                    result,
+                }) => {
+                    call_callback(
+                        callback,
+                        "webview_eval_result",
+                        &json!({
+                            "id": webview_id,
+                            "request_id": request_id,
+                            "value": result["value"],
+                            "error": result["error"]
+                        }),
+                    );
+                }
+
+                Event::UserEvent(CustomEvent::ProtocolRequest {
+                    webview_id,
+                    request_id,
+                    protocol,
+                    method,
+                    url,
+                    headers,
+                    body,
+                }) => {
+                    call_callback(
+                        callback,
+                        "protocol_request",
+                        &json!({
+                            "id": webview_id,
+                            "request_id": request_id,
+                            "protocol": protocol,
+                            "method": method,
+                            "url": url,
+                            "headers": headers,
+                            "body": body
+                        }),
+                    );
+                }
+
+                Event::UserEvent(CustomEvent::WebViewPageLoad {
+                    webview_id,
+                    event,
+                    url,
+                    history,
+                }) => {
+                    let (event_name, data) = match event {
+                        PageLoadEvent::Started => (
+                            "webview_page_load_started",
+                            json!({"id": webview_id, "url": url}),
+                        ),
+                        PageLoadEvent::Finished => {
+                            let (can_go_back, can_go_forward) = history.unwrap_or_default();
+                            let data = json!({
+                                "id": webview_id,
+                                "url": url,
+                                "can_go_back": can_go_back,
+                                "can_go_forward": can_go_forward
+                            });
+                            ("webview_page_load_finished", data)
+                        }
+                    };
+                    call_callback(callback, event_name, &data);
+                }
+
+                Event::UserEvent(CustomEvent::WebViewNewWindowRequested {
+                    webview_id,
+                    url,
+                    size,
+                    position,
+                }) => {
+                    let size = size.map(|size| json!({"width": size.width, "height": size.height}));
+                    let position =
+                        position.map(|position| json!({"x": position.x, "y": position.y}));
+                    call_callback(
+                        callback,
+                        "webview_new_window_requested",
+                        &json!({
+                            "id": webview_id,
+                            "url": url,
+                            "size": size,
+                            "position": position
+                        }),
+                    );
+                }
+
+                Event::UserEvent(CustomEvent::WebContextDataCleared {
+                    request_id,
+                    success,
+                }) => {
+                    call_callback(
+                        callback,
+                        "webcontext_data_cleared",
+                        &json!({
+                            "request_id": request_id,
+                            "success": success
+                        }),
+                    );
+                }
+
+                Event::UserEvent(CustomEvent::WebViewSnapshot {
+                    webview_id,
+                    request_id,
+                    path,
+                    result,
+                }) => {
+                    let (data, error) = match result {
+                        Ok(png) => (png.map(|png| BASE64.encode(png)), None),
+                        Err(error) => (None, Some(error)),
+                    };
+                    call_callback(
+                        callback,
+                        "webview_snapshot",
+                        &json!({
+                            "id": webview_id,
+                            "request_id": request_id,
+                            "path": path,
+                            "data": data,
+                            "error": error
+                        }),
+                    );
+                }
+
+                Event::UserEvent(CustomEvent::WebViewPdfPrinted {
+                    webview_id,
+                    request_id,
+                    path,
+                    error,
+                }) => {
+                    call_callback(
+                        callback,
+                        "webview_pdf_printed",
+                        &json!({
+                            "id": webview_id,
+                            "request_id": request_id,
+                            "path": path,
+                            "success": error.is_none(),
+                            "error": error
+                        }),
+                    );
+                }
+
+                Event::UserEvent(CustomEvent::WebViewTitleChanged { webview_id, title }) => {
+                    call_callback(
+                        callback,
+                        "webview_title_changed",
+                        &json!({
+                            "id": webview_id,
+                            "title": title
+                        }),
+                    );
+                }
+
+                Event::UserEvent(CustomEvent::WebViewDownloadProgress {
+                    webview_id,
+                    url,
+                    received_bytes,
+                    total_bytes,
+                    progress,
+                }) => {
+                    call_callback(
+                        callback,
+                        "webview_download_progress",
+                        &json!({
+                            "id": webview_id,
+                            "url": url,
+                            "received_bytes": received_bytes,
+                            "total_bytes": total_bytes,
+                            "progress": progress
+                        }),
+                    );
+                }
+
+                Event::UserEvent(CustomEvent::WebViewDownloadCompleted {
+                    webview_id,
+                    url,
+                    path,
+                    success,
+                }) => {
+                    call_callback(
+                        callback,
+                        "webview_download_completed",
+                        &json!({
+                            "id": webview_id,
+                            "url": url,
+                            "path": path.map(|path| path.to_string_lossy().to_string()),
+                            "success": success
+                        }),
+                    );
+                }
+
+                Event::MainEventsCleared => {
+                    *control_flow = ControlFlow::Exit;
+                }
+                _ => (),
+            }
+        },
+    );
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn rod_set_decision_callback(callback: Option<DecisionCallback>) {
+    let mut decision_callback = DECISION_CALLBACK.lock().unwrap();
+    *decision_callback = callback;
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_window_create(
+    event_loop_ptr: *mut c_void,
+    window_id: u16,
+    options_str_ptr: *mut c_void,
+) -> *mut c_void {
+    let event_loop = event_loop_from_ptr(event_loop_ptr);
+    let options_str = string_from_ptr(options_str_ptr);
+    let options: Value = serde_json::from_str(&options_str).unwrap();
+
+    let mut builder = WindowBuilder::new();
+
+    if options["always_on_bottom"].is_boolean() {
+        builder = builder.with_always_on_bottom(options["always_on_bottom"].as_bool().unwrap());
+    }
+
+    if options["always_on_top"].is_boolean() {
+        builder = builder.with_always_on_top(options["always_on_top"].as_bool().unwrap());
+    }
+
+    if options["closable"].is_boolean() {
+        builder = builder.with_closable(options["closable"].as_bool().unwrap());
+    }
+
+    if options["content_protection"].is_boolean() {
+        builder = builder.with_content_protection(options["content_protection"].as_bool().unwrap());
+    }
+
+    if options["decorations"] == false {
+        builder = builder.with_decorations(false);
+    }
+
+    if options["focusable"].is_boolean() {
+        builder = builder.with_focusable(options["focusable"].as_bool().unwrap());
+    }
+
+    if options["focused"].is_boolean() {
+        builder = builder.with_focused(options["focused"].as_bool().unwrap());
+    }
+
+    if options["size"].is_object() {
+        let width = options["size"]["width"].as_u64().unwrap() as f64;
+        let height = options["size"]["height"].as_u64().unwrap() as f64;
+        builder = builder.with_inner_size(LogicalSize::new(width, height));
+    }
+
+    if options["minimum_size"].is_object() {
+        let width = options["minimum_size"]["width"].as_u64().unwrap() as f64;
+        let height = options["minimum_size"]["height"].as_u64().unwrap() as f64;
+        builder = builder.with_min_inner_size(LogicalSize::new(width, height));
+    }
+
+    if options["maximum_size"].is_object() {
+        let width = options["maximum_size"]["width"].as_u64().unwrap() as f64;
+        let height = options["maximum_size"]["height"].as_u64().unwrap() as f64;
+        builder = builder.with_max_inner_size(LogicalSize::new(width, height));
+    }
+
+    if options["maximizable"].is_boolean() {
+        builder = builder.with_maximizable(options["maximizable"].as_bool().unwrap());
+    }
+
+    if options["maximized"].is_boolean() {
+        builder = builder.with_maximized(options["maximized"].as_bool().unwrap());
+    }
+
+    if options["minimizable"].is_boolean() {
+        builder = builder.with_minimizable(options["minimizable"].as_bool().unwrap());
+    }
+
+    if options["position"].is_object() {
+        let x = options["position"]["x"].as_i64().unwrap() as f64;
+        let y = options["position"]["y"].as_i64().unwrap() as f64;
+        builder = builder.with_position(LogicalPosition::new(x, y));
+    }
+
+    if options["resizable"].is_boolean() {
+        builder = builder.with_resizable(options["resizable"].as_bool().unwrap());
+    }
+
+    if options["title"].is_string() {
+        let title = options["title"].as_str().unwrap();
+        builder = builder.with_title(title);
+    }
+
+    if options["transparent"].is_boolean() {
+        builder = builder.with_transparent(options["transparent"].as_bool().unwrap());
+
+        #[cfg(target_os = "windows")]
+        {
+            use tao::platform::windows::WindowBuilderExtWindows;
+            builder = builder.with_undecorated_shadow(false);
+        }
+    }
+
+    if options["visible"].is_boolean() {
+        builder = builder.with_visible(options["visible"].as_bool().unwrap());
+    }
+
+    if options["visible_on_all_workspaces"].is_boolean() {
+        builder = builder.with_visible_on_all_workspaces(
+            options["visible_on_all_workspaces"].as_bool().unwrap(),
+        );
+    }
+
+    let window = builder.build(event_loop).unwrap();
+
+    // Store mapping from tao WindowId -> user-provided id
+    let tao_id = window.id();
+    {
+        let mut map = WINDOW_ID_MAP.lock().unwrap();
+        map.insert(tao_id, window_id);
+    }
+
+    return window_to_ptr(window);
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_window_destroy(window_ptr: *mut c_void) {
+    let window = window_from_ptr(window_ptr);
+    remove_window_id(&window.id());
+
+    unsafe {
+        drop(Box::from_raw(window_ptr as *mut Window));
+    }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_window_get_title(window_ptr: *mut c_void) -> *const c_char {
+    let window = window_from_ptr(window_ptr);
+    return string_to_ptr(window.title().as_str()).into_raw();
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_window_get_size(window_ptr: *mut c_void) -> *const c_char {
+    let window = window_from_ptr(window_ptr);
+    let size = window.inner_size();
+    return string_to_ptr(&json!({"width": size.width, "height": size.height}).to_string())
+        .into_raw();
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_window_get_position(window_ptr: *mut c_void) -> *const c_char {
+    let window = window_from_ptr(window_ptr);
+    let position = window.outer_position().unwrap();
+    return string_to_ptr(&json!({"x": position.x, "y": position.y}).to_string()).into_raw();
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_window_get_always_on_top(window_ptr: *mut c_void) -> bool {
+    let window = window_from_ptr(window_ptr);
+    return window.is_always_on_top();
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_window_get_closable(window_ptr: *mut c_void) -> bool {
+    let window = window_from_ptr(window_ptr);
+    return window.is_closable();
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_window_get_decorated(window_ptr: *mut c_void) -> bool {
+    let window = window_from_ptr(window_ptr);
+    return window.is_decorated();
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_window_get_focused(window_ptr: *mut c_void) -> bool {
+    let window = window_from_ptr(window_ptr);
+    return window.is_focused();
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_window_get_maximizable(window_ptr: *mut c_void) -> bool {
+    let window = window_from_ptr(window_ptr);
+    return window.is_maximizable();
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_window_get_maximized(window_ptr: *mut c_void) -> bool {
+    let window = window_from_ptr(window_ptr);
+    return window.is_maximized();
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_window_get_minimizable(window_ptr: *mut c_void) -> bool {
+    let window = window_from_ptr(window_ptr);
+    return window.is_minimizable();
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_window_get_minimized(window_ptr: *mut c_void) -> bool {
+    let window = window_from_ptr(window_ptr);
+    return window.is_minimized();
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_window_get_resizable(window_ptr: *mut c_void) -> bool {
+    let window = window_from_ptr(window_ptr);
+    return window.is_resizable();
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_window_get_visible(window_ptr: *mut c_void) -> bool {
+    let window = window_from_ptr(window_ptr);
+    return window.is_visible();
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_window_set_always_on_bottom(
+    window_ptr: *mut c_void,
+    always_on_bottom: bool,
+) {
+    let window = window_from_ptr(window_ptr);
+    window.set_always_on_bottom(always_on_bottom);
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_window_set_always_on_top(
+    window_ptr: *mut c_void,
+    always_on_top: bool,
+) {
+    let window = window_from_ptr(window_ptr);
+    window.set_always_on_top(always_on_top);
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_window_set_closable(window_ptr: *mut c_void, closable: bool) {
+    let window = window_from_ptr(window_ptr);
+    window.set_closable(closable);
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_window_set_content_protection(
+    window_ptr: *mut c_void,
+    content_protection: bool,
+) {
+    let window = window_from_ptr(window_ptr);
+    window.set_content_protection(content_protection);
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_window_set_decorations(window_ptr: *mut c_void, decorations: bool) {
+    let window = window_from_ptr(window_ptr);
+    window.set_decorations(decorations);
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_window_set_focus(window_ptr: *mut c_void) {
+    let window = window_from_ptr(window_ptr);
+    window.set_focus();
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_window_set_focusable(window_ptr: *mut c_void, focusable: bool) {
+    let window = window_from_ptr(window_ptr);
+    window.set_focusable(focusable);
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_window_set_fullscreen(window_ptr: *mut c_void, fullscreen: bool) {
+    let window = window_from_ptr(window_ptr);
+    if fullscreen {
+        window.set_fullscreen(Some(Borderless(None)));
+        return;
+    } else {
+        window.set_fullscreen(None);
+    }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_window_set_ignore_cursor_events(
+    window_ptr: *mut c_void,
+    ignore_cursor_events: bool,
+) {
+    let window = window_from_ptr(window_ptr);
+    window
+        .set_ignore_cursor_events(ignore_cursor_events)
+        .unwrap();
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_window_set_size(window_ptr: *mut c_void, size_str_ptr: *mut c_void) {
+    let window = window_from_ptr(window_ptr);
+    let size_str = string_from_ptr(size_str_ptr);
+    let size: Value = serde_json::from_str(&size_str).unwrap();
+    let width = size["width"].as_f64().unwrap();
+    let height = size["height"].as_f64().unwrap();
+    window.set_inner_size(PhysicalSize::new(width, height));
 }
 
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_window_set_focusable(window_ptr: *mut c_void, focusable: bool) {
+pub unsafe extern "C" fn rod_window_set_maximum_size(
+    window_ptr: *mut c_void,
+    maximum_size_str_ptr: *mut c_void,
+) {
     let window = window_from_ptr(window_ptr);
-    window.set_focusable(focusable);
+    let maximum_size_str = string_from_ptr(maximum_size_str_ptr);
+
+    if maximum_size_str.trim().is_empty() {
+        window.set_max_inner_size(None::<PhysicalSize<f64>>);
+        return;
+    }
+
+    let maximum_size: Value = serde_json::from_str(&maximum_size_str).unwrap();
+    let width = maximum_size["width"].as_f64().unwrap();
+    let height = maximum_size["height"].as_f64().unwrap();
+    window.set_max_inner_size(Some(PhysicalSize::new(width, height)));
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_window_set_maximizable(window_ptr: *mut c_void, maximizable: bool) {
+    let window = window_from_ptr(window_ptr);
+    window.set_maximizable(maximizable);
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_window_set_maximized(window_ptr: *mut c_void, maximized: bool) {
+    let window = window_from_ptr(window_ptr);
+    window.set_maximized(maximized);
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_window_set_minimum_size(
+    window_ptr: *mut c_void,
+    minimum_size_str_ptr: *mut c_void,
+) {
+    let window = window_from_ptr(window_ptr);
+    let minimum_size_str = string_from_ptr(minimum_size_str_ptr);
+
+    if minimum_size_str.trim().is_empty() {
+        window.set_min_inner_size(None::<PhysicalSize<f64>>);
+        return;
+    }
+
+    let minimum_size: Value = serde_json::from_str(&minimum_size_str).unwrap();
+    let width = minimum_size["width"].as_f64().unwrap();
+    let height = minimum_size["height"].as_f64().unwrap();
+    window.set_min_inner_size(Some(PhysicalSize::new(width, height)));
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_window_set_minimizable(window_ptr: *mut c_void, minimizable: bool) {
+    let window = window_from_ptr(window_ptr);
+    window.set_minimizable(minimizable);
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_window_set_minimized(window_ptr: *mut c_void, minimized: bool) {
+    let window = window_from_ptr(window_ptr);
+    window.set_minimized(minimized);
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_window_set_position(
+    window_ptr: *mut c_void,
+    position_str_ptr: *mut c_void,
+) {
+    let window = window_from_ptr(window_ptr);
+    let position_size_str = string_from_ptr(position_str_ptr);
+    let position: Value = serde_json::from_str(&position_size_str).unwrap();
+    let x = position["x"].as_f64().unwrap();
+    let y = position["y"].as_f64().unwrap();
+    window.set_outer_position(PhysicalPosition::new(x, y));
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_window_set_progress_bar(
+    window_ptr: *mut c_void,
+    progress_bar_str_ptr: *mut c_void,
+) {
+    let window = window_from_ptr(window_ptr);
+    let progress_bar_str = string_from_ptr(progress_bar_str_ptr);
+    let progress_bar: Value = serde_json::from_str(&progress_bar_str).unwrap();
+    let state = progress_bar["state"].as_f64().unwrap();
+    let progress = progress_bar["progress"].as_u64().unwrap();
+
+    let parsed_state = match state as i32 {
+        0 => ProgressState::Normal,
+        1 => ProgressState::Indeterminate,
+        2 => ProgressState::Paused,
+        3 => ProgressState::Error,
+        _ => ProgressState::None,
+    };
+
+    window.set_progress_bar(ProgressBarState {
+        state: Some(parsed_state),
+        progress: Some(progress),
+        desktop_filename: None,
+    });
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_window_set_resizable(window_ptr: *mut c_void, resizable: bool) {
+    let window = window_from_ptr(window_ptr);
+    window.set_resizable(resizable);
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_window_set_title(window_ptr: *mut c_void, title_str_ptr: *mut c_void) {
+    let window = window_from_ptr(window_ptr);
+    let title_str = string_from_ptr(title_str_ptr);
+    window.set_title(&title_str);
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_window_set_visible(window_ptr: *mut c_void, visible: bool) {
+    let window = window_from_ptr(window_ptr);
+    window.set_visible(visible);
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_window_set_visible_on_all_workspaces(
+    window_ptr: *mut c_void,
+    visible_on_all_workspaces: bool,
+) {
+    let window = window_from_ptr(window_ptr);
+    window.set_visible_on_all_workspaces(visible_on_all_workspaces);
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_window_start_drag(window_ptr: *mut c_void) {
+    let window = window_from_ptr(window_ptr);
+    window.drag_window().unwrap();
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn rod_webcontext_create(path_str_ptr: *mut c_void) -> *mut c_void {
+    let path_str = string_from_ptr(path_str_ptr);
+
+    // Contexts without a data directory back incognito webviews. WebKitGTK gives each of them an
+    // ephemeral context of its own, so there's nothing to create.
+    #[cfg(target_os = "linux")]
+    if path_str.is_empty() {
+        return std::ptr::null_mut();
+    }
+
+    // WebView2 still needs a user data folder for its browser process, each incognito context
+    // gets a temporary one that is removed once the context is released.
+    #[cfg(target_os = "windows")]
+    if path_str.is_empty() {
+        let path = std::env::temp_dir().join(format!(
+            "rod_incognito_{}_{}",
+            std::process::id(),
+            INCOGNITO_INDEX.fetch_add(1, Ordering::Relaxed)
+        ));
+        let webcontext_ptr = webcontext_to_ptr(WebContext::new(Some(path.clone())));
+        retain_webcontext(webcontext_ptr);
+        let mut directories = INCOGNITO_DIRECTORY_MAP.lock().unwrap();
+        directories.insert(webcontext_ptr as usize, path);
+        return webcontext_ptr;
+    }
+
+    return open_webcontext(Path::new(&path_str));
+}
+
+// Opens the named profile inside `directory`, webviews using the same profile share one context.
+#[unsafe(no_mangle)]
+pub extern "C" fn rod_webcontext_open_profile(
+    directory_str_ptr: *mut c_void,
+    name_str_ptr: *mut c_void,
+) -> *mut c_void {
+    let directory = string_from_ptr(directory_str_ptr);
+    let name = string_from_ptr(name_str_ptr);
+    let Some(path) = profile_path(&directory, &name) else {
+        set_last_error(format!("Invalid profile name {name}"));
+        return std::ptr::null_mut();
+    };
+    return open_webcontext(&path);
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn rod_webcontext_list_profiles(directory_str_ptr: *mut c_void) -> *const c_char {
+    let directory = string_from_ptr(directory_str_ptr);
+
+    let mut profiles = Vec::new();
+    if let Ok(entries) = std::fs::read_dir(&directory) {
+        for entry in entries.flatten() {
+            if !entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+                continue;
+            }
+
+            let path = entry.path();
+            profiles.push(json!({
+                "name": entry.file_name().to_string_lossy(),
+                "path": path.to_string_lossy(),
+                "in_use": is_profile_open(&path)
+            }));
+        }
+    }
+
+    return string_to_ptr(&Value::Array(profiles).to_string()).into_raw();
+}
+
+// Profiles still used by a webview or handle are never deleted.
+#[unsafe(no_mangle)]
+pub extern "C" fn rod_webcontext_delete_profile(
+    directory_str_ptr: *mut c_void,
+    name_str_ptr: *mut c_void,
+) -> bool {
+    let directory = string_from_ptr(directory_str_ptr);
+    let name = string_from_ptr(name_str_ptr);
+    let Some(path) = profile_path(&directory, &name) else {
+        return false;
+    };
+
+    if is_profile_open(&path) {
+        return false;
+    }
+    return std::fs::remove_dir_all(path).is_ok();
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_webcontext_destroy(webcontext_ptr: *mut c_void) {
+    release_webcontext(webcontext_ptr);
+}
+
+// A null proxy restores the system proxy settings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_webcontext_set_proxy(
+    webcontext_ptr: *mut c_void,
+    proxy_str_ptr: *mut c_void,
+) -> bool {
+    let proxy_str = string_from_ptr(proxy_str_ptr);
+    let proxy: Value = serde_json::from_str(&proxy_str).unwrap_or_default();
+
+    #[cfg(target_os = "windows")]
+    {
+        // WebView2 reads the proxy from the browser arguments when the environment is created.
+        let _ = (webcontext_ptr, proxy);
+        set_last_error("WebView2 can only set the proxy when a webview is created".to_string());
+        return false;
+    }
+    #[cfg(target_os = "linux")]
+    {
+        use webkit2gtk::{
+            NetworkProxyMode, NetworkProxySettings, WebContextExt, WebViewExt,
+            WebsiteDataManagerExt,
+        };
+        use wry::WebViewExtUnix;
+        if webcontext_ptr.is_null() {
+            set_last_error("Incognito webviews don't share a context".to_string());
+            return false;
+        }
+        let Some(webview_ptr) = get_webcontext_webview(webcontext_ptr) else {
+            set_last_error("No webview is using this context".to_string());
+            return false;
+        };
+        let Some(manager) = webview_from_ptr(webview_ptr)
+            .webview()
+            .context()
+            .and_then(|context| context.website_data_manager())
+        else {
+            set_last_error("The context has no website data manager".to_string());
+            return false;
+        };
+
+        if proxy.is_null() {
+            manager.set_network_proxy_settings(NetworkProxyMode::Default, None);
+            set_webcontext_proxy(webcontext_ptr, None);
+            return true;
+        }
+
+        let Some(proxy_uri) = proxy_config_from_value(&proxy).as_ref().map(proxy_uri) else {
+            set_last_error(format!("Invalid proxy {proxy}"));
+            return false;
+        };
+        let mut settings = NetworkProxySettings::new(Some(proxy_uri.as_str()), &[]);
+        manager.set_network_proxy_settings(NetworkProxyMode::Custom, Some(&mut settings));
+        set_webcontext_proxy(webcontext_ptr, Some(proxy_uri));
+        return true;
+    }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_webcontext_clear_data(
+    webcontext_ptr: *mut c_void,
+    request_id: u32,
+    types_str_ptr: *mut c_void,
+    since_timestamp: f64,
+    origins_str_ptr: *mut c_void,
+) -> bool {
+    let types_str = string_from_ptr(types_str_ptr);
+    let types: Vec<String> = serde_json::from_str(&types_str).unwrap_or_default();
+    let origins_str = string_from_ptr(origins_str_ptr);
+    let origins: Vec<String> = serde_json::from_str(&origins_str).unwrap_or_default();
+
+    // Neither platform clears a time range for single origins.
+    if since_timestamp != 0.0 && !origins.is_empty() {
+        set_last_error("Data can't be cleared for origins since a time".to_string());
+        return false;
+    }
+
+    if webcontext_ptr.is_null() {
+        set_last_error("Incognito webviews don't share a context".to_string());
+        return false;
+    }
+    let Some(webview_ptr) = get_webcontext_webview(webcontext_ptr) else {
+        set_last_error("No webview is using this context".to_string());
+        return false;
+    };
+    let webview = webview_from_ptr(webview_ptr);
+    return clear_browsing_data(webview, request_id, &types, since_timestamp, origins);
+}
+
+// The cookie store belongs to the context, but wry only reaches it through a webview, so a
+// webview must be using the context.
+fn webcontext_cookie_webview(webcontext_ptr: *mut c_void) -> Option<&'static WebView> {
+    if webcontext_ptr.is_null() {
+        set_last_error("Incognito webviews don't share a context".to_string());
+        return None;
+    }
+    let Some(webview_ptr) = get_webcontext_webview(webcontext_ptr) else {
+        set_last_error("No webview is using this context".to_string());
+        return None;
+    };
+    return Some(webview_from_ptr(webview_ptr));
+}
+
+// Lists the cookies of the whole store, or those sent to `url`. Returns null when the store
+// can't be reached.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_webcontext_get_cookies(
+    webcontext_ptr: *mut c_void,
+    url_ptr: *mut c_void,
+) -> *const c_char {
+    let url = string_from_ptr(url_ptr);
+    let Some(webview) = webcontext_cookie_webview(webcontext_ptr) else {
+        return string_to_ptr("null").into_raw();
+    };
+
+    let cookies = if url.is_empty() {
+        webview.cookies()
+    } else {
+        webview.cookies_for_url(&url)
+    };
+    let cookies = match cookies {
+        Ok(cookies) => cookies,
+        Err(error) => {
+            set_last_error(error.to_string());
+            return string_to_ptr("null").into_raw();
+        }
+    };
+
+    let cookies: Vec<Value> = cookies.iter().map(cookie_to_value).collect();
+    return string_to_ptr(&Value::Array(cookies).to_string()).into_raw();
+}
+
+// Deletes every cookie of the store, or those sent to `url`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_webcontext_delete_cookies(
+    webcontext_ptr: *mut c_void,
+    url_ptr: *mut c_void,
+) -> bool {
+    let url = string_from_ptr(url_ptr);
+    let Some(webview) = webcontext_cookie_webview(webcontext_ptr) else {
+        return false;
+    };
+
+    let cookies = if url.is_empty() {
+        webview.cookies()
+    } else {
+        webview.cookies_for_url(&url)
+    };
+    let cookies = match cookies {
+        Ok(cookies) => cookies,
+        Err(error) => {
+            set_last_error(error.to_string());
+            return false;
+        }
+    };
+
+    for cookie in &cookies {
+        if let Err(error) = webview.delete_cookie(cookie) {
+            set_last_error(error.to_string());
+            return false;
+        }
+    }
+    return true;
 }
 
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_window_set_fullscreen(window_ptr: *mut c_void, fullscreen: bool) {
-    let window = window_from_ptr(window_ptr);
-    if fullscreen {
-        window.set_fullscreen(Some(Borderless(None)));
-        return;
-    } else {
-        window.set_fullscreen(None);
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_webview_create(
+    window_ptr: *mut c_void,
+    webcontext_ptr: *mut c_void,
+    options_str_ptr: *mut c_void,
+) -> *mut c_void {
+    let window = window_from_ptr(window_ptr);
+    // Incognito webviews on Linux come without a context, wry creates an ephemeral one for them.
+    let webcontext = (!webcontext_ptr.is_null()).then(|| webcontext_from_ptr(webcontext_ptr));
+    let options_str = string_from_ptr(options_str_ptr);
+    let options: Value = serde_json::from_str(&options_str).unwrap();
+
+    // Events are tagged with the webview's own id, several webviews can share one window. The id
+    // also keys the asset servers and protocol handlers, so it has to be unique.
+    let Some(webview_id) = options["id"].as_u64().and_then(|id| u16::try_from(id).ok()) else {
+        set_last_error("The webview id must be between 0 and 65535".to_string());
+        return std::ptr::null_mut();
+    };
+    if get_webview_by_id(webview_id).is_some() {
+        set_last_error(format!("A webview with id {webview_id} already exists"));
+        return std::ptr::null_mut();
+    }
+
+    let accept_language = options["accept_language"].as_str();
+    #[cfg(target_os = "windows")]
+    if accept_language.is_some() {
+        set_last_error("WebView2 does not support setting the accept language".to_string());
+        return std::ptr::null_mut();
+    }
+
+    // The proxy and languages apply to the whole context, a webview joining a context in use
+    // can't change them.
+    let proxy_config = proxy_config_from_value(&options["proxy"]);
+    let proxy = proxy_config.as_ref().map(proxy_uri);
+    let context_in_use = webcontext.is_some() && get_webcontext_webview(webcontext_ptr).is_some();
+    if context_in_use && proxy.is_some() && proxy != get_webcontext_proxy(webcontext_ptr) {
+        set_last_error("The profile is already in use with a different proxy".to_string());
+        return std::ptr::null_mut();
+    }
+    if context_in_use
+        && accept_language.is_some()
+        && accept_language != get_webcontext_language(webcontext_ptr).as_deref()
+    {
+        set_last_error("The profile is already in use with different languages".to_string());
+        return std::ptr::null_mut();
+    }
+
+    let mut protocols: Vec<&str> = options["custom_protocols"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|protocol| protocol.as_str())
+        .collect();
+
+    if let Some(asset_directories) = options["asset_directories"].as_array() {
+        for asset_directory in asset_directories {
+            let (Some(protocol), Some(directory)) = (
+                asset_directory["protocol"].as_str(),
+                asset_directory["directory"].as_str(),
+            ) else {
+                continue;
+            };
+
+            let asset_server = AssetServer::new(
+                AssetDirectory::new(PathBuf::from(directory)),
+                asset_directory["host"].as_str().map(str::to_string),
+                asset_directory["spa_fallback"].as_bool().unwrap_or(true),
+            );
+            insert_asset_server(webview_id, protocol, asset_server);
+            protocols.push(protocol);
+        }
+    }
+
+    if let Some(asset_archives) = options["asset_archives"].as_array() {
+        for asset_archive in asset_archives {
+            let (Some(protocol), Some(archive)) = (
+                asset_archive["protocol"].as_str(),
+                asset_archive["archive"].as_str(),
+            ) else {
+                continue;
+            };
+
+            let archive = match AssetArchive::open(
+                std::path::Path::new(archive),
+                asset_archive["integrity"].as_str(),
+            ) {
+                Ok(archive) => archive,
+                Err(error) => {
+                    remove_asset_servers(webview_id);
+                    set_last_error(format!("Failed to mount asset archive {archive}: {error}"));
+                    return std::ptr::null_mut();
+                }
+            };
+
+            let asset_server = AssetServer::new(
+                archive,
+                asset_archive["host"].as_str().map(str::to_string),
+                asset_archive["spa_fallback"].as_bool().unwrap_or(true),
+            );
+            insert_asset_server(webview_id, protocol, asset_server);
+            protocols.push(protocol);
+        }
+    }
+
+    // A scheme is registered once per web context, later webviews are served by that handler.
+    let mut new_protocols = Vec::new();
+    for protocol in protocols {
+        let registered = webcontext
+            .as_ref()
+            .is_some_and(|webcontext| webcontext.is_custom_protocol_registered(protocol));
+        if !registered && !new_protocols.contains(&protocol) {
+            new_protocols.push(protocol);
+        }
+    }
+
+    let webview_id_str = webview_id.to_string();
+    let mut builder = match webcontext {
+        Some(webcontext) => WebViewBuilder::new_with_web_context(webcontext),
+        None => WebViewBuilder::new(),
+    }
+    .with_id(&webview_id_str);
+
+    for protocol in new_protocols {
+        builder = with_protocol(builder, protocol);
     }
-}
 
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_window_set_ignore_cursor_events(
-    window_ptr: *mut c_void,
-    ignore_cursor_events: bool,
-) {
-    let window = window_from_ptr(window_ptr);
-    window
-        .set_ignore_cursor_events(ignore_cursor_events)
-        .unwrap();
+    builder = builder.with_initialization_script_for_main_only(INVOKE_SCRIPT, true);
+
+    if let Some(initialization_scripts) = options["initialization_scripts"].as_array() {
+        for initialization_script in initialization_scripts {
+            let Some(script) = initialization_script["script"].as_str() else {
+                continue;
+            };
+            let main_frame_only = initialization_script["main_frame_only"]
+                .as_bool()
+                .unwrap_or(false);
+            builder = builder.with_initialization_script_for_main_only(script, main_frame_only);
+        }
+    }
+
+    // Asking Bun blocks every navigation on a round trip, it's only done for webviews created
+    // with a navigation handler.
+    let navigation_policy = NavigationPolicy::from_options(&options["navigation"]);
+    let navigation_handler = options["navigation_handler"] == true;
+    if navigation_handler || !navigation_policy.is_empty() {
+        builder = builder.with_navigation_handler(move |url| {
+            if !navigation_policy.allows(&url) {
+                return false;
+            }
+            if !navigation_handler {
+                return true;
+            }
+
+            let decision = request_decision(
+                "webview_navigation",
+                &json!({
+                    "id": webview_id,
+                    "url": url
+                }),
+            );
+            decision["allow"].as_bool().unwrap_or(true)
+        });
+    }
+
+    match options["new_window_behavior"].as_str() {
+        Some("deny") => {
+            builder = builder.with_new_window_req_handler(|_, _| NewWindowResponse::Deny);
+        }
+        Some("browser") => {
+            builder = builder.with_new_window_req_handler(|url, _| {
+                // Only hand web links to the system, pages should not be able to launch arbitrary
+                // handlers like `file:` through `window.open`.
+                if ["http:", "https:", "mailto:"]
+                    .iter()
+                    .any(|scheme| url.starts_with(scheme))
+                {
+                    let _ = open::that_detached(&url);
+                }
+                NewWindowResponse::Deny
+            });
+        }
+        Some("event") => {
+            builder = builder.with_new_window_req_handler(move |url, features| {
+                send_custom_event(CustomEvent::WebViewNewWindowRequested {
+                    webview_id,
+                    url,
+                    size: features.size,
+                    position: features.position,
+                });
+                NewWindowResponse::Deny
+            });
+        }
+        _ => (),
+    }
+
+    // Linux connects its download handlers on the web context after the webview is built.
+    #[cfg(target_os = "windows")]
+    {
+        builder = builder.with_download_started_handler(move |url, path| {
+            return decide_download(webview_id, &url, path);
+        });
+        builder = builder.with_download_completed_handler(move |url, path, success| {
+            send_custom_event(CustomEvent::WebViewDownloadCompleted {
+                webview_id,
+                url,
+                path,
+                success,
+            });
+        });
+    }
+
+    // History only settles once a load finishes, at the start it still describes the previous
+    // page. The webview is looked up by id as it has been built by then, and is unregistered
+    // before it is dropped.
+    builder = builder.with_on_page_load_handler(move |event, url| {
+        let history = match event {
+            PageLoadEvent::Started => None,
+            PageLoadEvent::Finished => get_webview_by_id(webview_id)
+                .map(|webview_ptr| history_state(webview_from_ptr(webview_ptr))),
+        };
+
+        send_custom_event(CustomEvent::WebViewPageLoad {
+            webview_id,
+            event,
+            url,
+            history,
+        });
+    });
+
+    // The window is looked up on every change, the webview may have been moved to another one.
+    let sync_window_title = options["sync_window_title"] == true;
+    builder = builder.with_document_title_changed_handler(move |title| {
+        if sync_window_title
+            && let Some(window_ptr) = get_webview_by_id(webview_id).and_then(get_webview_window)
+        {
+            window_from_ptr(window_ptr).set_title(&title);
+        }
+
+        send_custom_event(CustomEvent::WebViewTitleChanged { webview_id, title });
+    });
+
+    builder = builder.with_ipc_handler(move |request| {
+        let url = request.uri().to_string();
+        let message = request.into_body();
+
+        if let Some(invoke_str) = message.strip_prefix(INVOKE_MESSAGE_PREFIX) {
+            let invoke: Value = serde_json::from_str(invoke_str).unwrap_or_default();
+            let call_id = invoke["id"].as_u64().and_then(|id| u32::try_from(id).ok());
+            let (Some(call_id), Some(command)) = (call_id, invoke["command"].as_str()) else {
+                return;
+            };
+
+            send_custom_event(CustomEvent::WebViewInvoke {
+                webview_id,
+                call_id,
+                command: command.to_string(),
+                args: invoke["args"].clone(),
+            });
+            return;
+        }
+
+        if let Some(eval_str) = message.strip_prefix(EVAL_MESSAGE_PREFIX) {
+            let result: Value = serde_json::from_str(eval_str).unwrap_or_default();
+            let Some(request_id) = result["id"].as_u64().and_then(|id| u32::try_from(id).ok())
+            else {
+                return;
+            };
+
+            send_custom_event(CustomEvent::WebViewEvalResult {
+                webview_id,
+                request_id,
+                result,
+            });
+            return;
+        }
+
+        send_custom_event(CustomEvent::WebViewIpcMessage {
+            webview_id,
+            url,
+            message,
+        });
+    });
+
+    // Incognito webviews get an ephemeral session from wry, browsing data is never persisted.
+    if options["incognito"].is_boolean() {
+        builder = builder.with_incognito(options["incognito"].as_bool().unwrap());
+    }
+
+    if let Some(proxy_config) = proxy_config {
+        builder = builder.with_proxy_config(proxy_config);
+    }
+
+    if options["autoplay"].is_boolean() {
+        builder = builder.with_autoplay(options["autoplay"].as_bool().unwrap());
+    }
+
+    if options["back_forward_navigation_gestures"].is_boolean() {
+        builder = builder.with_back_forward_navigation_gestures(
+            options["back_forward_navigation_gestures"]
+                .as_bool()
+                .unwrap(),
+        );
+    }
+
+    if options["dev_tools"].is_boolean() {
+        builder = builder.with_devtools(options["dev_tools"].as_bool().unwrap());
+    }
+
+    if options["hotkeys_zoom"].is_boolean() {
+        builder = builder.with_hotkeys_zoom(options["hotkeys_zoom"].as_bool().unwrap());
+    }
+
+    // WebKitGTK takes the languages from the context of the built webview, the first page is
+    // loaded after they are set so its requests carry them too.
+    let defer_load = cfg!(target_os = "linux") && accept_language.is_some();
+
+    if options["html"].is_string() && !defer_load {
+        let html = options["html"].as_str().unwrap();
+        builder = builder.with_html(html);
+    }
+
+    if options["url"].is_string() && !defer_load {
+        let url = options["url"].as_str().unwrap();
+        builder = builder.with_url(url);
+    }
+
+    if options["headers"].is_object() && !defer_load {
+        builder = builder.with_headers(header_map_from_value(&options["headers"]));
+    }
+
+    if options["transparent"].is_boolean() {
+        builder = builder.with_transparent(options["transparent"].as_bool().unwrap());
+    }
+
+    // Painted before the page is, so dark pages don't flash white while loading.
+    if let Some(background_color) = color_from_value(&options["background_color"]) {
+        builder = builder.with_background_color(background_color);
+    }
+
+    if options["user_agent"].is_string() {
+        builder = builder.with_user_agent(options["user_agent"].as_str().unwrap());
+    }
+
+    // Webviews with bounds are children of the window, several can share it. Without bounds the
+    // webview fills the whole window.
+    let bounds = rect_from_value(&options["bounds"]);
+    if let Some(bounds) = bounds {
+        builder = builder.with_bounds(bounds);
+    }
+
+    #[cfg(target_os = "windows")]
+    let webview = match bounds {
+        Some(_) => builder.build_as_child(window).unwrap(),
+        None => builder.build(window).unwrap(),
+    };
+    #[cfg(target_os = "linux")]
+    let webview = {
+        use tao::platform::unix::WindowExtUnix;
+        use wry::WebViewBuilderExtUnix;
+        let vbox = window.default_vbox().unwrap();
+        match (bounds, find_layout(vbox)) {
+            (Some(bounds), _) => {
+                let webview = builder.build_gtk(&vbox_layout(vbox)).unwrap();
+                set_bounds(&webview, bounds);
+                webview
+            }
+            (None, Some(layout)) => {
+                let webview = builder.build_gtk(&layout).unwrap();
+                fill_window(&webview);
+                webview
+            }
+            (None, None) => builder.build_gtk(vbox).unwrap(),
+        }
+    };
+
+    #[cfg(target_os = "linux")]
+    connect_downloads(&webview);
+    #[cfg(target_os = "windows")]
+    connect_download_progress(&webview, webview_id);
+
+    let webview_ptr = webview_to_ptr(webview);
+    insert_webview_id(webview_ptr, webview_id);
+    insert_webview_window(webview_ptr, window_ptr);
+    if !webcontext_ptr.is_null() {
+        insert_webview_webcontext(webview_ptr, webcontext_ptr);
+        retain_webcontext(webcontext_ptr);
+    }
+    let webview = webview_from_ptr(webview_ptr);
+
+    // A webview that can't take its languages is torn down like a destroyed one.
+    if let Some(accept_language) = accept_language {
+        if !set_accept_language(webview, accept_language) {
+            unsafe { rod_webview_destroy(webview_ptr) };
+            return std::ptr::null_mut();
+        }
+        if !webcontext_ptr.is_null() {
+            set_webcontext_language(webcontext_ptr, accept_language);
+        }
+    }
+
+    // The first webview on a context decides its proxy, once it's built with it.
+    if !webcontext_ptr.is_null() && !context_in_use {
+        set_webcontext_proxy(webcontext_ptr, proxy);
+    }
+
+    if defer_load {
+        if let Some(url) = options["url"].as_str() {
+            let headers = header_map_from_value(&options["headers"]);
+            let _ = webview.load_url_with_headers(url, headers);
+        } else if let Some(html) = options["html"].as_str() {
+            let _ = webview.load_html(html);
+        }
+    }
+    return webview_ptr;
 }
 
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_window_set_size(window_ptr: *mut c_void, size_str_ptr: *mut c_void) {
-    let window = window_from_ptr(window_ptr);
-    let size_str = string_from_ptr(size_str_ptr);
-    let size: Value = serde_json::from_str(&size_str).unwrap();
-    let width = size["width"].as_f64().unwrap();
-    let height = size["height"].as_f64().unwrap();
-    window.set_inner_size(PhysicalSize::new(width, height));
+pub unsafe extern "C" fn rod_webview_destroy(webview_ptr: *mut c_void) {
+    if let Some(webview_id) = get_webview_id(webview_ptr) {
+        fail_protocol_responders(webview_id);
+        remove_asset_servers(webview_id);
+    }
+    remove_webview_id(webview_ptr);
+    remove_webview_window(webview_ptr);
+    let webcontext_ptr = remove_webview_webcontext(webview_ptr);
+
+    unsafe {
+        drop(Box::from_raw(webview_ptr as *mut WebView));
+    }
+
+    if let Some(webcontext_ptr) = webcontext_ptr {
+        release_webcontext(webcontext_ptr);
+    }
 }
 
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_window_set_maximum_size(
-    window_ptr: *mut c_void,
-    maximum_size_str_ptr: *mut c_void,
-) {
-    let window = window_from_ptr(window_ptr);
-    let maximum_size_str = string_from_ptr(maximum_size_str_ptr);
+pub unsafe extern "C" fn rod_webview_set_bounds(
+    webview_ptr: *mut c_void,
+    bounds_ptr: *mut c_void,
+) -> bool {
+    let webview = webview_from_ptr(webview_ptr);
+    let bounds = string_from_ptr(bounds_ptr);
+    let bounds: Value = serde_json::from_str(&bounds).unwrap_or_default();
+    let Some(bounds) = rect_from_value(&bounds) else {
+        return false;
+    };
 
-    if maximum_size_str.trim().is_empty() {
-        window.set_max_inner_size(None::<PhysicalSize<f64>>);
-        return;
-    }
+    return set_bounds(webview, bounds);
+}
 
-    let maximum_size: Value = serde_json::from_str(&maximum_size_str).unwrap();
-    let width = maximum_size["width"].as_f64().unwrap();
-    let height = maximum_size["height"].as_f64().unwrap();
-    window.set_max_inner_size(Some(PhysicalSize::new(width, height)));
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_webview_get_bounds(webview_ptr: *mut c_void) -> *const c_char {
+    let webview = webview_from_ptr(webview_ptr);
+    let bounds = get_bounds(webview);
+    return string_to_ptr(&bounds.to_string()).into_raw();
 }
 
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_window_set_maximizable(window_ptr: *mut c_void, maximizable: bool) {
-    let window = window_from_ptr(window_ptr);
-    window.set_maximizable(maximizable);
+pub unsafe extern "C" fn rod_webview_bring_to_front(webview_ptr: *mut c_void) -> bool {
+    let webview = webview_from_ptr(webview_ptr);
+    return restack_webview(webview, true);
 }
 
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_window_set_maximized(window_ptr: *mut c_void, maximized: bool) {
-    let window = window_from_ptr(window_ptr);
-    window.set_maximized(maximized);
+pub unsafe extern "C" fn rod_webview_send_to_back(webview_ptr: *mut c_void) -> bool {
+    let webview = webview_from_ptr(webview_ptr);
+    return restack_webview(webview, false);
 }
 
+// Moves a live webview into another window, the page keeps running. A webview with bounds keeps
+// them and becomes a child of the new window, otherwise it fills the new window.
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_window_set_minimum_size(
+pub unsafe extern "C" fn rod_webview_reparent(
+    webview_ptr: *mut c_void,
     window_ptr: *mut c_void,
-    minimum_size_str_ptr: *mut c_void,
-) {
+) -> bool {
+    let webview = webview_from_ptr(webview_ptr);
     let window = window_from_ptr(window_ptr);
-    let minimum_size_str = string_from_ptr(minimum_size_str_ptr);
 
-    if minimum_size_str.trim().is_empty() {
-        window.set_min_inner_size(None::<PhysicalSize<f64>>);
-        return;
+    #[cfg(target_os = "windows")]
+    let reparented = {
+        use tao::platform::windows::WindowExtWindows;
+        use wry::WebViewExtWindows;
+        webview.reparent(window.hwnd()).is_ok()
+    };
+    #[cfg(target_os = "linux")]
+    let reparented = {
+        use tao::platform::unix::WindowExtUnix;
+        use wry::WebViewExtUnix;
+        // The Layout puts the webview at the origin, its bounds or fill are restored after.
+        let vbox = window.default_vbox().unwrap();
+        let bounds = rect_from_value(&get_bounds(webview));
+        if is_filling(webview) {
+            match find_layout(vbox) {
+                Some(layout) => {
+                    let reparented = webview.reparent(&layout).is_ok();
+                    fill_window(webview);
+                    reparented
+                }
+                None => webview.reparent(vbox).is_ok(),
+            }
+        } else {
+            let reparented = webview.reparent(&vbox_layout(vbox)).is_ok();
+            if let Some(bounds) = bounds {
+                set_bounds(webview, bounds);
+            }
+            reparented
+        }
+    };
+
+    // A moved webview goes on top of the ones already in the window.
+    if reparented {
+        insert_webview_window(webview_ptr, window_ptr);
+        restack_webview(webview, true);
     }
+    return reparented;
+}
 
-    let minimum_size: Value = serde_json::from_str(&minimum_size_str).unwrap();
-    let width = minimum_size["width"].as_f64().unwrap();
-    let height = minimum_size["height"].as_f64().unwrap();
-    window.set_min_inner_size(Some(PhysicalSize::new(width, height)));
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_webview_set_visible(webview_ptr: *mut c_void, visible: bool) {
+    let webview = webview_from_ptr(webview_ptr);
+    let _ = webview.set_visible(visible);
 }
 
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_window_set_minimizable(window_ptr: *mut c_void, minimizable: bool) {
-    let window = window_from_ptr(window_ptr);
-    window.set_minimizable(minimizable);
+pub unsafe extern "C" fn rod_webview_focus(webview_ptr: *mut c_void) {
+    let webview = webview_from_ptr(webview_ptr);
+    let _ = webview.focus();
 }
 
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_window_set_minimized(window_ptr: *mut c_void, minimized: bool) {
-    let window = window_from_ptr(window_ptr);
-    window.set_minimized(minimized);
+pub unsafe extern "C" fn rod_webview_focus_parent(webview_ptr: *mut c_void) {
+    let webview = webview_from_ptr(webview_ptr);
+    let _ = webview.focus_parent();
 }
 
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_window_set_position(
-    window_ptr: *mut c_void,
-    position_str_ptr: *mut c_void,
-) {
-    let window = window_from_ptr(window_ptr);
-    let position_size_str = string_from_ptr(position_str_ptr);
-    let position: Value = serde_json::from_str(&position_size_str).unwrap();
-    let x = position["x"].as_f64().unwrap();
-    let y = position["y"].as_f64().unwrap();
-    window.set_outer_position(PhysicalPosition::new(x, y));
+pub unsafe extern "C" fn rod_webview_set_background_color(
+    webview_ptr: *mut c_void,
+    color_ptr: *mut c_void,
+) -> bool {
+    let webview = webview_from_ptr(webview_ptr);
+    let color = Value::String(string_from_ptr(color_ptr));
+    let Some(background_color) = color_from_value(&color) else {
+        return false;
+    };
+
+    return webview.set_background_color(background_color).is_ok();
 }
 
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_window_set_progress_bar(
-    window_ptr: *mut c_void,
-    progress_bar_str_ptr: *mut c_void,
-) {
-    let window = window_from_ptr(window_ptr);
-    let progress_bar_str = string_from_ptr(progress_bar_str_ptr);
-    let progress_bar: Value = serde_json::from_str(&progress_bar_str).unwrap();
-    let state = progress_bar["state"].as_f64().unwrap();
-    let progress = progress_bar["progress"].as_u64().unwrap();
+pub unsafe extern "C" fn rod_webview_get_url(webview_ptr: *mut c_void) -> *const c_char {
+    let webview = webview_from_ptr(webview_ptr);
+    match webview.url() {
+        Ok(url) => string_to_ptr(&url).into_raw(),
+        Err(_) => string_to_ptr("").into_raw(),
+    }
+}
 
-    let parsed_state = match state as i32 {
-        0 => ProgressState::Normal,
-        1 => ProgressState::Indeterminate,
-        2 => ProgressState::Paused,
-        3 => ProgressState::Error,
-        _ => ProgressState::None,
-    };
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_webview_is_devtools_open(webview_ptr: *mut c_void) -> bool {
+    let webview = webview_from_ptr(webview_ptr);
+    webview.is_devtools_open()
+}
 
-    window.set_progress_bar(ProgressBarState {
-        state: Some(parsed_state),
-        progress: Some(progress),
-        desktop_filename: None,
-    });
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_webview_set_url(webview_ptr: *mut c_void, url_ptr: *mut c_void) {
+    let webview = webview_from_ptr(webview_ptr);
+    let url = string_from_ptr(url_ptr);
+    webview.load_url(&url).unwrap();
+    return;
 }
 
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_window_set_resizable(window_ptr: *mut c_void, resizable: bool) {
-    let window = window_from_ptr(window_ptr);
-    window.set_resizable(resizable);
+pub unsafe extern "C" fn rod_webview_open_devtools(webview_ptr: *mut c_void) {
+    let webview = webview_from_ptr(webview_ptr);
+    webview.open_devtools();
 }
 
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_window_set_title(window_ptr: *mut c_void, title_str_ptr: *mut c_void) {
-    let window = window_from_ptr(window_ptr);
-    let title_str = string_from_ptr(title_str_ptr);
-    window.set_title(&title_str);
+pub unsafe extern "C" fn rod_webview_close_devtools(webview_ptr: *mut c_void) {
+    let webview = webview_from_ptr(webview_ptr);
+    webview.close_devtools();
 }
 
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_window_set_visible(window_ptr: *mut c_void, visible: bool) {
-    let window = window_from_ptr(window_ptr);
-    window.set_visible(visible);
+pub unsafe extern "C" fn rod_webview_zoom(webview_ptr: *mut c_void, scale_factor: f64) {
+    let webview = webview_from_ptr(webview_ptr);
+    let _ = webview.zoom(scale_factor);
 }
 
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_window_set_visible_on_all_workspaces(
-    window_ptr: *mut c_void,
-    visible_on_all_workspaces: bool,
-) {
-    let window = window_from_ptr(window_ptr);
-    window.set_visible_on_all_workspaces(visible_on_all_workspaces);
+pub unsafe extern "C" fn rod_webview_reload(webview_ptr: *mut c_void) {
+    let webview = webview_from_ptr(webview_ptr);
+    let _ = webview.reload();
 }
 
+// Cookies live in the webview's WebContext, every webview sharing that context sees the same store.
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_window_start_drag(window_ptr: *mut c_void) {
-    let window = window_from_ptr(window_ptr);
-    window.drag_window().unwrap();
+pub unsafe extern "C" fn rod_webview_get_cookies(
+    webview_ptr: *mut c_void,
+    url_ptr: *mut c_void,
+) -> *const c_char {
+    let webview = webview_from_ptr(webview_ptr);
+    let url = string_from_ptr(url_ptr);
+
+    let cookies = if url.is_empty() {
+        webview.cookies()
+    } else {
+        webview.cookies_for_url(&url)
+    }
+    .unwrap_or_default();
+
+    let cookies: Vec<Value> = cookies.iter().map(cookie_to_value).collect();
+    return string_to_ptr(&Value::Array(cookies).to_string()).into_raw();
 }
 
 #[unsafe(no_mangle)]
-pub extern "C" fn rod_webcontext_create(path_str_ptr: *mut c_void) -> *mut c_void {
-    let path_str = string_from_ptr(path_str_ptr);
-    let path = PathBuf::from(path_str);
-    let webcontext = WebContext::new(Some(path));
-    return webcontext_to_ptr(webcontext);
+pub unsafe extern "C" fn rod_webview_set_cookie(
+    webview_ptr: *mut c_void,
+    cookie_str_ptr: *mut c_void,
+) -> bool {
+    let webview = webview_from_ptr(webview_ptr);
+    let cookie_str = string_from_ptr(cookie_str_ptr);
+    let cookie: Value = serde_json::from_str(&cookie_str).unwrap_or_default();
+
+    let Some(cookie) = cookie_from_value(&cookie) else {
+        return false;
+    };
+    return webview.set_cookie(&cookie).is_ok();
 }
 
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_webcontext_destroy(webcontext_ptr: *mut c_void) {
-    unsafe {
-        drop(Box::from_raw(webcontext_ptr as *mut WebContext));
-    }
+pub unsafe extern "C" fn rod_webview_delete_cookie(
+    webview_ptr: *mut c_void,
+    cookie_str_ptr: *mut c_void,
+) -> bool {
+    let webview = webview_from_ptr(webview_ptr);
+    let cookie_str = string_from_ptr(cookie_str_ptr);
+    let cookie: Value = serde_json::from_str(&cookie_str).unwrap_or_default();
+
+    let Some(cookie) = cookie_from_value(&cookie) else {
+        return false;
+    };
+    return webview.delete_cookie(&cookie).is_ok();
 }
 
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_webview_create(
-    window_ptr: *mut c_void,
-    webcontext_ptr: *mut c_void,
-    options_str_ptr: *mut c_void,
-) -> *mut c_void {
-    let window = window_from_ptr(window_ptr);
-    let webcontext = webcontext_from_ptr(webcontext_ptr);
-    let options_str = string_from_ptr(options_str_ptr);
-    let options: Value = serde_json::from_str(&options_str).unwrap();
-
-    let mut builder = WebViewBuilder::new_with_web_context(webcontext);
+pub unsafe extern "C" fn rod_webview_load_request(
+    webview_ptr: *mut c_void,
+    request_str_ptr: *mut c_void,
+) -> bool {
+    let webview = webview_from_ptr(webview_ptr);
+    let request_str = string_from_ptr(request_str_ptr);
+    let request: Value = serde_json::from_str(&request_str).unwrap_or_default();
 
-    if options["autoplay"].is_boolean() {
-        builder = builder.with_autoplay(options["autoplay"].as_bool().unwrap());
+    let Some(url) = request["url"].as_str() else {
+        return false;
+    };
+    let method = request["method"].as_str().unwrap_or("GET").to_uppercase();
+    let headers = header_map_from_value(&request["headers"]);
+    let body = request["body"]
+        .as_str()
+        .and_then(|body| BASE64.decode(body).ok())
+        .unwrap_or_default();
+
+    if method == "GET" && body.is_empty() {
+        return webview.load_url_with_headers(url, headers).is_ok();
     }
 
-    if options["back_forward_navigation_gestures"].is_boolean() {
-        builder = builder.with_back_forward_navigation_gestures(
-            options["back_forward_navigation_gestures"]
-                .as_bool()
-                .unwrap(),
-        );
+    #[cfg(target_os = "windows")]
+    {
+        use webview2_com::Microsoft::Web::WebView2::Win32::{
+            ICoreWebView2_10, ICoreWebView2Environment9,
+        };
+        use windows::Win32::UI::Shell::SHCreateMemStream;
+        use windows::core::{HSTRING, Interface};
+        use wry::WebViewExtWindows;
+        let header_lines: String = headers
+            .iter()
+            .filter_map(|(name, value)| {
+                value
+                    .to_str()
+                    .ok()
+                    .map(|value| format!("{name}: {value}\r\n"))
+            })
+            .collect();
+
+        let navigate = || -> windows::core::Result<()> {
+            unsafe {
+                let environment = webview.environment().cast::<ICoreWebView2Environment9>()?;
+                let post_data = if body.is_empty() {
+                    None
+                } else {
+                    SHCreateMemStream(Some(&body))
+                };
+                let request = environment.CreateWebResourceRequest(
+                    &HSTRING::from(url),
+                    &HSTRING::from(method.as_str()),
+                    post_data.as_ref(),
+                    &HSTRING::from(header_lines),
+                )?;
+                webview
+                    .webview()
+                    .cast::<ICoreWebView2_10>()?
+                    .NavigateWithWebResourceRequest(&request)
+            }
+        };
+        if let Err(error) = navigate() {
+            set_last_error(error.message());
+            return false;
+        }
+        return true;
     }
-
-    if options["dev_tools"].is_boolean() {
-        builder = builder.with_devtools(options["dev_tools"].as_bool().unwrap());
+    // WebKitGTK only navigates with GET.
+    #[cfg(target_os = "linux")]
+    {
+        set_last_error(format!(
+            "WebKitGTK can only load GET requests without a body, got {method}{}",
+            if body.is_empty() { "" } else { " with a body" }
+        ));
+        return false;
     }
+}
 
-    if options["hotkeys_zoom"].is_boolean() {
-        builder = builder.with_hotkeys_zoom(options["hotkeys_zoom"].as_bool().unwrap());
-    }
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_webview_set_user_agent(
+    webview_ptr: *mut c_void,
+    user_agent_ptr: *mut c_void,
+) -> bool {
+    let webview = webview_from_ptr(webview_ptr);
+    let user_agent = string_from_ptr(user_agent_ptr);
 
-    if options["html"].is_string() {
-        let html = options["html"].as_str().unwrap();
-        builder = builder.with_html(html);
+    #[cfg(target_os = "windows")]
+    {
+        use webview2_com::Microsoft::Web::WebView2::Win32::ICoreWebView2Settings2;
+        use windows::core::{HSTRING, Interface};
+        use wry::WebViewExtWindows;
+        let set_user_agent = || -> windows::core::Result<()> {
+            unsafe {
+                webview
+                    .webview()
+                    .Settings()?
+                    .cast::<ICoreWebView2Settings2>()?
+                    .SetUserAgent(&HSTRING::from(user_agent))
+            }
+        };
+        return set_user_agent().is_ok();
+    }
+    #[cfg(target_os = "linux")]
+    {
+        use webkit2gtk::{SettingsExt, WebViewExt};
+        use wry::WebViewExtUnix;
+        let Some(settings) = WebViewExt::settings(&webview.webview()) else {
+            return false;
+        };
+        settings.set_user_agent(Some(&user_agent));
+        return true;
     }
+}
 
-    if options["url"].is_string() {
-        let url = options["url"].as_str().unwrap();
-        builder = builder.with_url(url);
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_webview_set_accept_language(
+    webview_ptr: *mut c_void,
+    accept_language_ptr: *mut c_void,
+) -> bool {
+    let webview = webview_from_ptr(webview_ptr);
+    let accept_language = string_from_ptr(accept_language_ptr);
+    if !set_accept_language(webview, &accept_language) {
+        return false;
     }
+    if let Some(webcontext_ptr) = get_webview_webcontext(webview_ptr) {
+        set_webcontext_language(webcontext_ptr, &accept_language);
+    }
+    return true;
+}
 
-    if options["transparent"].is_boolean() {
-        builder = builder.with_transparent(options["transparent"].as_bool().unwrap());
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_webview_go_back(webview_ptr: *mut c_void) {
+    let webview = webview_from_ptr(webview_ptr);
+
+    #[cfg(target_os = "windows")]
+    {
+        use wry::WebViewExtWindows;
+        let _ = unsafe { webview.webview().GoBack() };
+    }
+    #[cfg(target_os = "linux")]
+    {
+        use webkit2gtk::WebViewExt;
+        use wry::WebViewExtUnix;
+        webview.webview().go_back();
     }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_webview_go_forward(webview_ptr: *mut c_void) {
+    let webview = webview_from_ptr(webview_ptr);
 
     #[cfg(target_os = "windows")]
-    let webview = builder.build(window).unwrap();
+    {
+        use wry::WebViewExtWindows;
+        let _ = unsafe { webview.webview().GoForward() };
+    }
     #[cfg(target_os = "linux")]
-    let webview = {
-        use tao::platform::unix::WindowExtUnix;
-        use wry::WebViewBuilderExtUnix;
-        let vbox = window.default_vbox().unwrap();
-        builder.build_gtk(vbox).unwrap()
-    };
+    {
+        use webkit2gtk::WebViewExt;
+        use wry::WebViewExtUnix;
+        webview.webview().go_forward();
+    }
+}
 
-    return webview_to_ptr(webview);
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_webview_can_go_back(webview_ptr: *mut c_void) -> bool {
+    let webview = webview_from_ptr(webview_ptr);
+    let (can_go_back, _) = history_state(webview);
+    return can_go_back;
 }
 
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_webview_destroy(webview_ptr: *mut c_void) {
-    unsafe {
-        drop(Box::from_raw(webview_ptr as *mut WebView));
-    }
+pub unsafe extern "C" fn rod_webview_can_go_forward(webview_ptr: *mut c_void) -> bool {
+    let webview = webview_from_ptr(webview_ptr);
+    let (_, can_go_forward) = history_state(webview);
+    return can_go_forward;
 }
 
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_webview_get_url(webview_ptr: *mut c_void) -> *const c_char {
+pub unsafe extern "C" fn rod_webview_stop(webview_ptr: *mut c_void) {
     let webview = webview_from_ptr(webview_ptr);
-    match webview.url() {
-        Ok(url) => string_to_ptr(&url).into_raw(),
-        Err(_) => string_to_ptr("").into_raw(),
+
+    #[cfg(target_os = "windows")]
+    {
+        use wry::WebViewExtWindows;
+        let _ = unsafe { webview.webview().Stop() };
+    }
+    #[cfg(target_os = "linux")]
+    {
+        use webkit2gtk::WebViewExt;
+        use wry::WebViewExtUnix;
+        webview.webview().stop_loading();
     }
 }
 
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_webview_is_devtools_open(webview_ptr: *mut c_void) -> bool {
+pub unsafe extern "C" fn rod_webview_set_html(webview_ptr: *mut c_void, html_ptr: *mut c_void) {
     let webview = webview_from_ptr(webview_ptr);
-    webview.is_devtools_open()
+    let html = string_from_ptr(html_ptr);
+    let _ = webview.load_html(&html);
 }
 
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_webview_set_url(webview_ptr: *mut c_void, url_ptr: *mut c_void) {
+pub unsafe extern "C" fn rod_webview_eval(webview_ptr: *mut c_void, script_ptr: *mut c_void) {
     let webview = webview_from_ptr(webview_ptr);
-    let url = string_from_ptr(url_ptr);
-    webview.load_url(&url).unwrap();
-    return;
+    let script = string_from_ptr(script_ptr);
+    let _ = webview.evaluate_script(&script);
 }
 
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_webview_open_devtools(webview_ptr: *mut c_void) {
+pub unsafe extern "C" fn rod_webview_eval_with_callback(
+    webview_ptr: *mut c_void,
+    request_id: u32,
+    script_ptr: *mut c_void,
+) -> bool {
     let webview = webview_from_ptr(webview_ptr);
-    webview.open_devtools();
+    let Some(webview_id) = get_webview_id(webview_ptr) else {
+        return false;
+    };
+
+    let script = string_from_ptr(script_ptr);
+    let wrapped_script = EVAL_WRAPPER_SCRIPT
+        .replace("__ROD_REQUEST_ID__", &request_id.to_string())
+        .replace("__ROD_SCRIPT__", &json!(script).to_string());
+
+    // The result only arrives as an event when the script was handed to the webview. Pending
+    // results arrive through the IPC handler once the promise settles.
+    return webview
+        .evaluate_script_with_callback(&wrapped_script, move |result| {
+            let result: Value = serde_json::from_str(&result).unwrap_or_default();
+            if result["pending"] == true {
+                return;
+            }
+
+            send_custom_event(CustomEvent::WebViewEvalResult {
+                webview_id,
+                request_id,
+                result,
+            });
+        })
+        .is_ok();
 }
 
+// Options are {full_document, path}, the result arrives as a webview_snapshot event.
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_webview_close_devtools(webview_ptr: *mut c_void) {
+pub unsafe extern "C" fn rod_webview_snapshot(
+    webview_ptr: *mut c_void,
+    request_id: u32,
+    options_ptr: *mut c_void,
+) -> bool {
     let webview = webview_from_ptr(webview_ptr);
-    webview.close_devtools();
+    let Some(webview_id) = get_webview_id(webview_ptr) else {
+        return false;
+    };
+
+    let options = string_from_ptr(options_ptr);
+    let options: Value = serde_json::from_str(&options).unwrap_or_default();
+    let full_document = options["full_document"] == true;
+    let path = options["path"].as_str().map(str::to_string);
+
+    capture_snapshot(webview, full_document, move |png| {
+        finish_snapshot(webview_id, request_id, path, png)
+    });
+    return true;
 }
 
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_webview_zoom(webview_ptr: *mut c_void, scale_factor: f64) {
+pub unsafe extern "C" fn rod_webview_print(webview_ptr: *mut c_void) {
     let webview = webview_from_ptr(webview_ptr);
-    let _ = webview.zoom(scale_factor);
+
+    // wry opens the GTK dialog without a parent, it is attached to the webview's window here.
+    #[cfg(target_os = "linux")]
+    {
+        use gtk::prelude::*;
+        use webkit2gtk::{PrintOperation, PrintOperationExt};
+        use wry::WebViewExtUnix;
+        let inner = webview.webview();
+        let parent = inner
+            .toplevel()
+            .and_then(|toplevel| toplevel.downcast::<gtk::Window>().ok());
+        PrintOperation::new(&inner).run_dialog(parent.as_ref());
+    }
+    #[cfg(target_os = "windows")]
+    let _ = webview.print();
 }
 
+// Options are {path, page_size, margins, landscape}, the result arrives as a webview_pdf_printed
+// event.
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_webview_reload(webview_ptr: *mut c_void) {
+pub unsafe extern "C" fn rod_webview_print_to_pdf(
+    webview_ptr: *mut c_void,
+    request_id: u32,
+    options_ptr: *mut c_void,
+) -> bool {
     let webview = webview_from_ptr(webview_ptr);
-    let _ = webview.reload();
+    let Some(webview_id) = get_webview_id(webview_ptr) else {
+        return false;
+    };
+
+    let options = string_from_ptr(options_ptr);
+    let options: Value = serde_json::from_str(&options).unwrap_or_default();
+    let path = options["path"].as_str().unwrap_or_default().to_string();
+    let page = pdf_page_from_value(&options);
+
+    let finished_path = path.clone();
+    print_to_pdf(webview, &path, page, move |error| {
+        send_custom_event(CustomEvent::WebViewPdfPrinted {
+            webview_id,
+            request_id,
+            path: finished_path.clone(),
+            error,
+        });
+    });
+    return true;
 }
 
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn rod_webview_set_html(webview_ptr: *mut c_void, html_ptr: *mut c_void) {
+pub unsafe extern "C" fn rod_webview_add_init_script(
+    webview_ptr: *mut c_void,
+    script_ptr: *mut c_void,
+    main_frame_only: bool,
+) {
     let webview = webview_from_ptr(webview_ptr);
-    let html = string_from_ptr(html_ptr);
-    let _ = webview.load_html(&html);
+    let script = string_from_ptr(script_ptr);
+
+    #[cfg(target_os = "windows")]
+    {
+        use webview2_com::AddScriptToExecuteOnDocumentCreatedCompletedHandler;
+        use windows::core::HSTRING;
+        use wry::WebViewExtWindows;
+        // WebView2 has no main frame only variant, the script is added to every document.
+        let _ = main_frame_only;
+        let core_webview = webview.webview();
+        let _ = AddScriptToExecuteOnDocumentCreatedCompletedHandler::wait_for_async_operation(
+            Box::new(move |handler| unsafe {
+                core_webview
+                    .AddScriptToExecuteOnDocumentCreated(&HSTRING::from(script), &handler)
+                    .map_err(Into::into)
+            }),
+            Box::new(|error_code, _| error_code),
+        );
+    }
+    #[cfg(target_os = "linux")]
+    {
+        use webkit2gtk::{
+            UserContentInjectedFrames, UserContentManagerExt, UserScript, UserScriptInjectionTime,
+            WebViewExt,
+        };
+        use wry::WebViewExtUnix;
+        let Some(manager) = webview.webview().user_content_manager() else {
+            return;
+        };
+        let injected_frames = if main_frame_only {
+            UserContentInjectedFrames::TopFrame
+        } else {
+            UserContentInjectedFrames::AllFrames
+        };
+        manager.add_script(&UserScript::new(
+            &script,
+            injected_frames,
+            UserScriptInjectionTime::Start,
+            &[],
+            &[],
+        ));
+    }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_webview_resolve_invoke(
+    webview_ptr: *mut c_void,
+    call_id: u32,
+    payload_str_ptr: *mut c_void,
+    is_error: bool,
+) -> bool {
+    let webview = webview_from_ptr(webview_ptr);
+    let payload_str = string_from_ptr(payload_str_ptr);
+
+    // A payload that isn't JSON rejects the page's call, Bun is told why as well.
+    let (payload, is_error, resolved) = match serde_json::from_str::<Value>(&payload_str) {
+        Ok(payload) => (payload, is_error, true),
+        Err(error) => {
+            let message = format!("Invalid invoke payload: {error}");
+            set_last_error(message.clone());
+            (json!({ "message": message }), true, false)
+        }
+    };
+    let script = format!("window.__rod_resolve_invoke({call_id}, {payload}, {is_error});");
+    let _ = webview.evaluate_script(&script);
+    return resolved;
 }
 
 #[unsafe(no_mangle)]
@@ -828,6 +4087,53 @@ pub unsafe extern "C" fn rod_webview_clear_all_browsing_data(webview_ptr: *mut c
     let _ = webview.clear_all_browsing_data();
 }
 
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_take_last_error() -> *const c_char {
+    let message = LAST_ERROR.lock().unwrap().take().unwrap_or_default();
+    return string_to_ptr(&message).into_raw();
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rod_protocol_respond(
+    request_id: u32,
+    status: u16,
+    headers_str_ptr: *mut c_void,
+    body_ptr: *const u8,
+    body_len: usize,
+) {
+    let Some(responder) = remove_protocol_responder(request_id) else {
+        return;
+    };
+
+    let headers_str = string_from_ptr(headers_str_ptr);
+    let headers: Value = serde_json::from_str(&headers_str).unwrap_or_default();
+
+    let body = if body_ptr.is_null() || body_len == 0 {
+        Vec::new()
+    } else {
+        unsafe { std::slice::from_raw_parts(body_ptr, body_len) }.to_vec()
+    };
+
+    // A header given as an array is sent once per value, e.g. several Set-Cookie headers.
+    let mut response = Response::builder().status(status);
+    if let Some(headers) = headers.as_object() {
+        for (name, value) in headers {
+            let values = match value {
+                Value::Array(values) => values.iter().filter_map(Value::as_str).collect(),
+                value => value.as_str().into_iter().collect::<Vec<_>>(),
+            };
+            for value in values {
+                response = response.header(name, value);
+            }
+        }
+    }
+
+    match response.body(body) {
+        Ok(response) => responder.respond(response),
+        Err(_) => responder.respond(Response::builder().status(500).body(Vec::new()).unwrap()),
+    }
+}
+
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn rod_tray_create(
     tray_id: u16,
@@ -866,3 +4172,229 @@ pub unsafe extern "C" fn rod_tray_destroy(tray_ptr: *mut c_void) {
         drop(Box::from_raw(tray_ptr as *mut TrayIcon));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_from_value_parses_hex_colors() {
+        assert_eq!(color_from_value(&json!("#000000")), Some((0, 0, 0, 255)));
+        assert_eq!(
+            color_from_value(&json!("#ff8000")),
+            Some((255, 128, 0, 255))
+        );
+        assert_eq!(
+            color_from_value(&json!("#FF8000")),
+            Some((255, 128, 0, 255))
+        );
+        assert_eq!(
+            color_from_value(&json!("#12345678")),
+            Some((0x12, 0x34, 0x56, 0x78))
+        );
+    }
+
+    #[test]
+    fn color_from_value_rejects_bad_hex() {
+        assert_eq!(color_from_value(&json!("ff8000")), None);
+        assert_eq!(color_from_value(&json!("#")), None);
+        assert_eq!(color_from_value(&json!("#fff")), None);
+        assert_eq!(color_from_value(&json!("#ff80000")), None);
+        assert_eq!(color_from_value(&json!("#ff8000000")), None);
+        assert_eq!(color_from_value(&json!("#gg8000")), None);
+        assert_eq!(color_from_value(&json!("#+f8000")), None);
+        assert_eq!(color_from_value(&json!("#-f8000")), None);
+        assert_eq!(color_from_value(&json!("# f8000")), None);
+        assert_eq!(color_from_value(&json!("#ff80é")), None);
+        assert_eq!(color_from_value(&json!(0xff8000)), None);
+        assert_eq!(color_from_value(&Value::Null), None);
+    }
+
+    #[test]
+    fn profile_path_only_accepts_a_single_directory() {
+        assert_eq!(
+            profile_path("profiles", "work"),
+            Some(Path::new("profiles").join("work"))
+        );
+        assert_eq!(profile_path("profiles", ""), None);
+        assert_eq!(profile_path("profiles", "."), None);
+        assert_eq!(profile_path("profiles", ".."), None);
+        assert_eq!(profile_path("profiles", "work/../.."), None);
+        assert_eq!(profile_path("profiles", "/etc"), None);
+        #[cfg(target_os = "windows")]
+        {
+            assert_eq!(profile_path("profiles", "C:"), None);
+            assert_eq!(profile_path("profiles", "work\\other"), None);
+        }
+    }
+
+    // A boxed byte stands in for the native context, only the bookkeeping is tested.
+    #[test]
+    fn open_webcontext_shares_and_releases_a_directory() {
+        let directory = std::env::temp_dir().join(format!("rod-profiles-{}", std::process::id()));
+        let path = directory.join("work");
+        let other_spelling = directory.join(".").join("work");
+        let create = |_: &Path| Box::into_raw(Box::new(0u8)) as *mut c_void;
+
+        let webcontext_ptr = open_webcontext_with(&path, create);
+        assert!(!webcontext_ptr.is_null());
+        assert_eq!(
+            open_webcontext_with(&other_spelling, |_| unreachable!()),
+            webcontext_ptr
+        );
+        assert!(is_profile_open(&path));
+
+        assert!(!release_webcontext_reference(webcontext_ptr));
+        assert!(is_profile_open(&other_spelling));
+        assert!(release_webcontext_reference(webcontext_ptr));
+        assert!(!is_profile_open(&path));
+        assert!(!release_webcontext_reference(webcontext_ptr));
+
+        unsafe { drop(Box::from_raw(webcontext_ptr as *mut u8)) };
+        let _ = std::fs::remove_dir_all(directory);
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "windows"))]
+    fn strings(values: &[&str]) -> Vec<String> {
+        return values.iter().map(|value| value.to_string()).collect();
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn clear_timespan_only_clears_everything_without_a_start() {
+        assert_eq!(clear_timespan(0.0, 1_000.0), Some(0));
+        assert_eq!(clear_timespan(990.0, 1_000.0), Some(10_000_000));
+        assert_eq!(clear_timespan(999.5, 1_000.0), Some(500_000));
+        assert_eq!(clear_timespan(1_000.0, 1_000.0), Some(1));
+        assert_eq!(clear_timespan(1_000.5, 1_000.0), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn website_data_types_maps_browsing_data_types() {
+        use webkit2gtk::WebsiteDataTypes;
+
+        assert_eq!(website_data_types(&[]), Ok(WebsiteDataTypes::empty()));
+        assert_eq!(
+            website_data_types(&strings(&["cookies", "local_storage"])),
+            Ok(WebsiteDataTypes::COOKIES | WebsiteDataTypes::LOCAL_STORAGE)
+        );
+        assert_eq!(
+            website_data_types(&strings(&["cache"])),
+            Ok(WebsiteDataTypes::MEMORY_CACHE
+                | WebsiteDataTypes::DISK_CACHE
+                | WebsiteDataTypes::OFFLINE_APPLICATION_CACHE
+                | WebsiteDataTypes::DOM_CACHE)
+        );
+        assert_eq!(
+            website_data_types(&strings(&["all"])),
+            Ok(WebsiteDataTypes::ALL)
+        );
+        assert!(website_data_types(&strings(&["cookies", "history"])).is_err());
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn browsing_data_kinds_maps_browsing_data_types() {
+        use webview2_com::Microsoft::Web::WebView2::Win32::*;
+
+        assert_eq!(
+            browsing_data_kinds(&strings(&["cookies", "indexeddb"])),
+            Ok(COREWEBVIEW2_BROWSING_DATA_KINDS_COOKIES.0
+                | COREWEBVIEW2_BROWSING_DATA_KINDS_INDEXED_DB.0)
+        );
+        assert!(browsing_data_kinds(&strings(&["session_storage"])).is_err());
+        assert!(browsing_data_kinds(&strings(&["history"])).is_err());
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn origin_storage_types_maps_browsing_data_types() {
+        assert_eq!(
+            origin_storage_types(&strings(&["cache", "cookies"])),
+            Ok("cache_storage,cookies".to_string())
+        );
+        assert!(origin_storage_types(&strings(&["hsts"])).is_err());
+        assert!(origin_storage_types(&strings(&["history"])).is_err());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    #[ignore = "needs a display, run with --ignored under Xvfb"]
+    fn restack_child_reorders_layout_children() {
+        use gtk::prelude::*;
+        gtk::init().expect("GTK needs a display");
+
+        let layout = gtk::Layout::new(None::<&gtk::Adjustment>, None::<&gtk::Adjustment>);
+        let labels: Vec<gtk::Widget> = (0..3)
+            .map(|index| gtk::Label::new(Some(&index.to_string())).upcast())
+            .collect();
+        for (index, label) in labels.iter().enumerate() {
+            layout.put(label, index as i32 * 10, 5);
+        }
+
+        restack_child(&layout, &labels[0], true);
+        assert_eq!(
+            layout.children(),
+            vec![labels[1].clone(), labels[2].clone(), labels[0].clone()]
+        );
+        assert_eq!(layout.child_property::<i32>(&labels[0], "x"), 0);
+
+        restack_child(&layout, &labels[2], false);
+        assert_eq!(
+            layout.children(),
+            vec![labels[2].clone(), labels[1].clone(), labels[0].clone()]
+        );
+        assert_eq!(layout.child_property::<i32>(&labels[2], "x"), 20);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    #[ignore = "needs a display, run with --ignored under Xvfb"]
+    fn print_to_pdf_writes_a_pdf() {
+        use gtk::prelude::*;
+        use std::time::{Duration, Instant};
+        use wry::WebViewBuilderExtUnix;
+        gtk::init().expect("GTK needs a display");
+
+        let window = gtk::Window::new(gtk::WindowType::Toplevel);
+        let container = gtk::Box::new(gtk::Orientation::Vertical, 0);
+        window.add(&container);
+
+        let loaded = Rc::new(Cell::new(false));
+        let page_loaded = loaded.clone();
+        let webview = WebViewBuilder::new()
+            .with_html("<h1>rod</h1>")
+            .with_on_page_load_handler(move |event, _| {
+                if let PageLoadEvent::Finished = event {
+                    page_loaded.set(true);
+                }
+            })
+            .build_gtk(&container)
+            .unwrap();
+        window.show_all();
+
+        let wait_for = |done: &dyn Fn() -> bool| {
+            let deadline = Instant::now() + Duration::from_secs(30);
+            while !done() && Instant::now() < deadline {
+                gtk::main_iteration_do(false);
+            }
+        };
+        wait_for(&|| loaded.get());
+        assert!(loaded.get(), "page did not load");
+
+        let path = std::env::temp_dir().join(format!("rod-print-{}.pdf", std::process::id()));
+        let result = Rc::new(std::cell::RefCell::new(None));
+        let printed = result.clone();
+        let page = pdf_page_from_value(&json!({ "page_size": "a4", "landscape": true }));
+        print_to_pdf(&webview, path.to_str().unwrap(), page, move |error| {
+            *printed.borrow_mut() = Some(error);
+        });
+        wait_for(&|| result.borrow().is_some());
+
+        assert_eq!(result.take(), Some(None), "printing did not finish");
+        let pdf = std::fs::read(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert!(pdf.starts_with(b"%PDF"));
+    }
+}
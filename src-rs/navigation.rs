@@ -0,0 +1,206 @@
+use serde_json::Value;
+
+/// Declarative allow and deny lists for the URLs a webview may navigate to.
+///
+/// Patterns match the whole URL and support `*` as a wildcard, e.g. `https://*.example.com/*`.
+pub struct NavigationPolicy {
+    allow: Vec<String>,
+    deny: Vec<String>,
+}
+
+impl NavigationPolicy {
+    pub fn from_options(options: &Value) -> Self {
+        let patterns = |key: &str| {
+            options[key]
+                .as_array()
+                .map(|patterns| {
+                    patterns
+                        .iter()
+                        .filter_map(|pattern| pattern.as_str().map(str::to_string))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        NavigationPolicy {
+            allow: patterns("allow"),
+            deny: patterns("deny"),
+        }
+    }
+
+    /// A policy without patterns allows every URL.
+    pub fn is_empty(&self) -> bool {
+        self.allow.is_empty() && self.deny.is_empty()
+    }
+
+    /// Deny patterns win over allow patterns. An empty allow list allows every URL that is not
+    /// denied.
+    pub fn allows(&self, url: &str) -> bool {
+        if self
+            .deny
+            .iter()
+            .any(|pattern| matches_pattern(pattern, url))
+        {
+            return false;
+        }
+
+        self.allow.is_empty()
+            || self
+                .allow
+                .iter()
+                .any(|pattern| matches_pattern(pattern, url))
+    }
+}
+
+// A wildcard followed by more of the host, like the first one in `https://*.example.com/*`,
+// does not match `/`, `?` or `#`. Otherwise `https://evil.com/.example.com/` would pass as a
+// subdomain. A wildcard ending the host, as in `https://*`, still matches the rest of the URL.
+fn matches_pattern(pattern: &str, value: &str) -> bool {
+    let pattern = pattern.as_bytes();
+    let value = value.as_bytes();
+    let host_end = host_end(pattern);
+
+    // matched[index] is true when the pattern read so far matches value[..index].
+    let mut matched = vec![false; value.len() + 1];
+    matched[0] = true;
+
+    for (pattern_index, &byte) in pattern.iter().enumerate() {
+        let mut next = vec![false; value.len() + 1];
+        if byte == b'*' {
+            let in_host = pattern_index + 1 < host_end;
+            let mut reachable = false;
+            for index in 0..=value.len() {
+                reachable |= matched[index];
+                next[index] = reachable;
+                if in_host && index < value.len() && b"/?#".contains(&value[index]) {
+                    reachable = false;
+                }
+            }
+        } else {
+            for index in 0..value.len() {
+                next[index + 1] = matched[index] && value[index] == byte;
+            }
+        }
+        matched = next;
+    }
+
+    matched[value.len()]
+}
+
+// Index in the pattern where the host ends, 0 when the pattern has no scheme.
+fn host_end(pattern: &[u8]) -> usize {
+    let Some(scheme_end) = pattern.windows(3).position(|window| window == b"://") else {
+        return 0;
+    };
+
+    let host_start = scheme_end + 3;
+    pattern[host_start..]
+        .iter()
+        .position(|byte| b"/?#".contains(byte))
+        .map_or(pattern.len(), |position| host_start + position)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn matches_exact_urls() {
+        assert!(matches_pattern(
+            "https://example.com/",
+            "https://example.com/"
+        ));
+        assert!(!matches_pattern(
+            "https://example.com/",
+            "https://example.com/a"
+        ));
+        assert!(!matches_pattern(
+            "https://example.com/a",
+            "https://example.com/"
+        ));
+        assert!(matches_pattern("", ""));
+        assert!(!matches_pattern("", "https://example.com/"));
+    }
+
+    #[test]
+    fn wildcards_match_any_run_of_characters() {
+        assert!(matches_pattern("*", ""));
+        assert!(matches_pattern("*", "https://example.com/"));
+        assert!(matches_pattern("**", "https://example.com/"));
+        assert!(matches_pattern(
+            "https://example.com/*",
+            "https://example.com/"
+        ));
+        assert!(matches_pattern(
+            "https://example.com/*",
+            "https://example.com/a/b?c#d"
+        ));
+        assert!(matches_pattern(
+            "*/app/*",
+            "https://example.com/app/index.html"
+        ));
+        assert!(matches_pattern("https://*", "https://example.com/a/b"));
+    }
+
+    #[test]
+    fn wildcards_backtrack() {
+        assert!(matches_pattern("*a*b", "xaxaxb"));
+        assert!(matches_pattern(
+            "https://example.com/*.js",
+            "https://example.com/a.js.js"
+        ));
+        assert!(!matches_pattern(
+            "https://example.com/*.js",
+            "https://example.com/a.jsx"
+        ));
+        assert!(!matches_pattern("*a", "bbb"));
+    }
+
+    #[test]
+    fn subdomain_wildcards_stay_in_the_host() {
+        let pattern = "https://*.example.com/*";
+        assert!(matches_pattern(pattern, "https://app.example.com/"));
+        assert!(matches_pattern(pattern, "https://a.b.example.com/path"));
+        assert!(!matches_pattern(pattern, "https://example.com/"));
+        assert!(!matches_pattern(pattern, "https://evil.com/.example.com/"));
+        assert!(!matches_pattern(pattern, "https://evil.com?.example.com/"));
+        assert!(!matches_pattern(pattern, "https://evil.com#.example.com/"));
+        assert!(!matches_pattern(pattern, "https://example.com.evil.com/"));
+    }
+
+    #[test]
+    fn matching_is_case_sensitive() {
+        assert!(!matches_pattern(
+            "https://example.com/",
+            "HTTPS://EXAMPLE.COM/"
+        ));
+    }
+
+    #[test]
+    fn deny_wins_over_allow() {
+        let policy = NavigationPolicy::from_options(&json!({
+            "allow": ["https://example.com/*"],
+            "deny": ["https://example.com/admin*"]
+        }));
+        assert!(policy.allows("https://example.com/"));
+        assert!(!policy.allows("https://example.com/admin/users"));
+        assert!(!policy.allows("https://other.com/"));
+    }
+
+    #[test]
+    fn empty_allow_list_allows_everything_not_denied() {
+        let policy = NavigationPolicy::from_options(&json!({ "deny": ["*://ads.*"] }));
+        assert!(policy.allows("https://example.com/"));
+        assert!(!policy.allows("https://ads.example.com/"));
+        assert!(NavigationPolicy::from_options(&Value::Null).allows("about:blank"));
+    }
+
+    #[test]
+    fn policies_without_patterns_are_empty() {
+        assert!(NavigationPolicy::from_options(&Value::Null).is_empty());
+        assert!(NavigationPolicy::from_options(&json!({ "allow": [], "deny": [] })).is_empty());
+        assert!(!NavigationPolicy::from_options(&json!({ "deny": ["*://ads.*"] })).is_empty());
+        assert!(!NavigationPolicy::from_options(&json!({ "allow": ["https://*"] })).is_empty());
+    }
+}
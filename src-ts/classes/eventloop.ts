@@ -1,11 +1,22 @@
-import { CString, FFIType, JSCallback, type Pointer } from "bun:ffi";
+import { CString, FFIType, JSCallback, type Pointer, ptr } from "bun:ffi";
 import { TypedEmitter } from "tiny-typed-emitter";
 import {
 	rod_event_loop_create,
 	rod_event_loop_destroy,
 	rod_event_loop_poll,
+	rod_set_decision_callback,
 } from "../ffi";
-import type { Position, Size } from "../types";
+import type {
+	DownloadCompleted,
+	DownloadDecision,
+	DownloadProgress,
+	DownloadRequest,
+	HistoryState,
+	NewWindowRequest,
+	Position,
+	ProtocolRequest,
+	Size,
+} from "../types";
 
 interface EventLoopEvents {
 	window_close_requested: (id: number) => void;
@@ -13,12 +24,67 @@ interface EventLoopEvents {
 	window_moved: (id: number, position: Position) => void;
 	window_resized: (id: number, size: Size) => void;
 	tray_clicked: (id: number) => void;
+	webview_ipc_message: (id: number, message: string, url: string) => void;
+	webview_invoke: (
+		id: number,
+		callId: number,
+		command: string,
+		args: unknown,
+	) => void;
+	webview_eval_result: (
+		id: number,
+		requestId: number,
+		value: unknown,
+		error: string | null,
+	) => void;
+	webview_snapshot: (
+		id: number,
+		requestId: number,
+		data: Uint8Array | null,
+		error: string | null,
+	) => void;
+	webview_pdf_printed: (
+		id: number,
+		requestId: number,
+		error: string | null,
+	) => void;
+	protocol_request: (
+		id: number,
+		requestId: number,
+		request: ProtocolRequest,
+	) => void;
+	webview_page_load_started: (id: number, url: string) => void;
+	webview_page_load_finished: (
+		id: number,
+		url: string,
+		history: HistoryState,
+	) => void;
+	webview_new_window_requested: (id: number, request: NewWindowRequest) => void;
+	webview_title_changed: (id: number, title: string) => void;
+	webcontext_data_cleared: (requestId: number, success: boolean) => void;
+	webview_download_progress: (id: number, progress: DownloadProgress) => void;
+	webview_download_completed: (
+		id: number,
+		completed: DownloadCompleted,
+	) => void;
+}
+
+interface EventLoopDecisions {
+	webview_navigation: (id: number, url: string) => boolean;
+	webview_download_started: (
+		id: number,
+		request: DownloadRequest,
+	) => DownloadDecision;
 }
 
 export default class EventLoop extends TypedEmitter<EventLoopEvents> {
 	eventloopPtr: Pointer;
 
 	private callback: JSCallback;
+	private decisionCallback: JSCallback;
+	private decisionHandlers: Partial<EventLoopDecisions>;
+	// Keeps the last decision response alive until the native side has copied it
+	private decisionResponse: Uint8Array | null;
 	private interval: NodeJS.Timeout;
 	constructor() {
 		super();
@@ -50,6 +116,86 @@ export default class EventLoop extends TypedEmitter<EventLoopEvents> {
 						});
 					case "tray_clicked":
 						return this.emit("tray_clicked", data.id);
+					case "webview_ipc_message":
+						return this.emit(
+							"webview_ipc_message",
+							data.id,
+							data.message,
+							data.url,
+						);
+					case "webview_invoke":
+						return this.emit(
+							"webview_invoke",
+							data.id,
+							data.call_id,
+							data.command,
+							data.args,
+						);
+					case "webview_eval_result":
+						return this.emit(
+							"webview_eval_result",
+							data.id,
+							data.request_id,
+							data.value,
+							data.error,
+						);
+					case "webview_snapshot":
+						return this.emit(
+							"webview_snapshot",
+							data.id,
+							data.request_id,
+							data.data === null ? null : Buffer.from(data.data, "base64"),
+							data.error,
+						);
+					case "webview_pdf_printed":
+						return this.emit(
+							"webview_pdf_printed",
+							data.id,
+							data.request_id,
+							data.error,
+						);
+					case "protocol_request":
+						return this.emit("protocol_request", data.id, data.request_id, {
+							protocol: data.protocol,
+							method: data.method,
+							url: data.url,
+							headers: data.headers,
+							body: Buffer.from(data.body, "base64"),
+						});
+					case "webview_page_load_started":
+						return this.emit("webview_page_load_started", data.id, data.url);
+					case "webview_page_load_finished":
+						return this.emit("webview_page_load_finished", data.id, data.url, {
+							canGoBack: data.can_go_back,
+							canGoForward: data.can_go_forward,
+						});
+					case "webview_new_window_requested":
+						return this.emit("webview_new_window_requested", data.id, {
+							url: data.url,
+							size: data.size,
+							position: data.position,
+						});
+					case "webcontext_data_cleared":
+						return this.emit(
+							"webcontext_data_cleared",
+							data.request_id,
+							data.success,
+						);
+					case "webview_title_changed":
+						return this.emit("webview_title_changed", data.id, data.title);
+					case "webview_download_progress":
+						return this.emit("webview_download_progress", data.id, {
+							url: data.url,
+							receivedBytes: data.received_bytes,
+							totalBytes: data.total_bytes,
+							progress: data.progress,
+						});
+					case "webview_download_completed":
+						return this.emit("webview_download_completed", data.id, {
+							url: data.url,
+							path: data.path,
+							success: data.success,
+						});
 				}
 
 				throw new Error(`Unknown event type: ${event}`);
@@ -60,13 +206,66 @@ export default class EventLoop extends TypedEmitter<EventLoopEvents> {
 			},
 		);
 
+		this.decisionHandlers = {};
+		this.decisionResponse = null;
+		this.decisionCallback = new JSCallback(
+			(eventPtr: Pointer, rawDataPtr: Pointer) => {
+				const event = new CString(eventPtr).toString();
+				const rawData = new CString(rawDataPtr).toString();
+				const data = JSON.parse(rawData);
+
+				// A null response makes the native side take the default decision.
+				// Exceptions must not escape into native code, they fall back to it.
+				let response = "null";
+				try {
+					let decision: unknown = null;
+					switch (event) {
+						case "webview_navigation": {
+							const handler = this.decisionHandlers.webview_navigation;
+							decision = { allow: handler ? handler(data.id, data.url) : true };
+							break;
+						}
+						case "webview_download_started": {
+							const handler = this.decisionHandlers.webview_download_started;
+							const request = {
+								url: data.url,
+								suggestedFilename: data.suggested_filename,
+								path: data.path,
+							};
+							decision = handler ? handler(data.id, request) : {};
+							break;
+						}
+					}
+					response = JSON.stringify(decision) ?? "null";
+				} catch (error) {
+					console.error(`Failed to decide ${event}:`, error);
+				}
+
+				this.decisionResponse = new TextEncoder().encode(`${response}\0`);
+				return ptr(this.decisionResponse);
+			},
+			{
+				args: [FFIType.cstring, FFIType.cstring],
+				returns: FFIType.ptr,
+			},
+		);
+		rod_set_decision_callback(this.decisionCallback);
+
 		this.interval = setInterval(() => {
 			rod_event_loop_poll(this.eventloopPtr, this.callback);
 		});
 	}
 
+	setDecisionHandler<U extends keyof EventLoopDecisions>(
+		decision: U,
+		handler: EventLoopDecisions[U],
+	) {
+		this.decisionHandlers[decision] = handler;
+	}
+
 	destroy() {
 		clearInterval(this.interval);
+		rod_set_decision_callback(null);
 		if (this.eventloopPtr) {
 			rod_event_loop_destroy(this.eventloopPtr);
 			this.eventloopPtr = null as unknown as Pointer;
@@ -1,5 +1,6 @@
 import type { TrayOptions, WebViewOptions, WindowOptions } from "../types";
 import EventLoop from "./eventloop";
+import WebContext, { DEFAULT_PROFILES_DIRECTORY } from "./webcontext";
 import Tray from "./tray";
 import WebView from "./webview";
 
@@ -38,6 +39,93 @@ export default class Rod {
 			webview.emit("resized", size);
 		});
 
+		this.eventLoop.on("webview_ipc_message", (id, message, url) => {
+			const webview = this.retrieveWebViewById(id);
+			if (!webview) return;
+			webview.emit("ipc_message", message, url);
+		});
+
+		this.eventLoop.on("webview_invoke", (id, callId, command, args) => {
+			const webview = this.retrieveWebViewById(id);
+			if (!webview) return;
+			webview.emit("invoke", callId, command, args);
+		});
+
+		this.eventLoop.on("webview_eval_result", (id, requestId, value, error) => {
+			const webview = this.retrieveWebViewById(id);
+			if (!webview) return;
+			webview.emit("eval_result", requestId, value, error);
+		});
+
+		this.eventLoop.on("webview_snapshot", (id, requestId, data, error) => {
+			const webview = this.retrieveWebViewById(id);
+			if (!webview) return;
+			webview.emit("snapshot", requestId, data, error);
+		});
+
+		this.eventLoop.on("webview_pdf_printed", (id, requestId, error) => {
+			const webview = this.retrieveWebViewById(id);
+			if (!webview) return;
+			webview.emit("pdf_printed", requestId, error);
+		});
+
+		this.eventLoop.on("protocol_request", (id, requestId, request) => {
+			const webview = this.retrieveWebViewById(id);
+			if (!webview) return;
+			webview.emit("protocol_request", requestId, request);
+		});
+
+		this.eventLoop.on("webview_page_load_started", (id, url) => {
+			const webview = this.retrieveWebViewById(id);
+			if (!webview) return;
+			webview.emit("page_load_started", url);
+		});
+
+		this.eventLoop.on("webview_page_load_finished", (id, url, history) => {
+			const webview = this.retrieveWebViewById(id);
+			if (!webview) return;
+			webview.emit("page_load_finished", url, history);
+		});
+
+		this.eventLoop.on("webview_new_window_requested", (id, request) => {
+			const webview = this.retrieveWebViewById(id);
+			if (!webview) return;
+			webview.emit("new_window_requested", request);
+		});
+
+		this.eventLoop.on("webview_title_changed", (id, title) => {
+			const webview = this.retrieveWebViewById(id);
+			if (!webview) return;
+			webview.emit("title_changed", title);
+		});
+
+		this.eventLoop.on("webview_download_progress", (id, progress) => {
+			const webview = this.retrieveWebViewById(id);
+			if (!webview) return;
+			webview.emit("download_progress", progress);
+		});
+
+		this.eventLoop.on("webview_download_completed", (id, completed) => {
+			const webview = this.retrieveWebViewById(id);
+			if (!webview) return;
+			webview.emit("download_completed", completed);
+		});
+
+		this.eventLoop.setDecisionHandler(
+			"webview_download_started",
+			(id, request) => {
+				const webview = this.retrieveWebViewById(id);
+				if (!webview) return {};
+				return webview.decideDownload(request);
+			},
+		);
+
+		this.eventLoop.setDecisionHandler("webview_navigation", (id, url) => {
+			const webview = this.retrieveWebViewById(id);
+			if (!webview) return true;
+			return webview.decideNavigation(url);
+		});
+
 		this.eventLoop.on("tray_clicked", (id) => {
 			const tray = this.trays.find((t) => t.id === id);
 			if (!tray) return;
@@ -65,6 +153,14 @@ export default class Rod {
 		return webview;
 	}
 
+	listProfiles(directory = DEFAULT_PROFILES_DIRECTORY) {
+		return WebContext.listProfiles(directory);
+	}
+
+	deleteProfile(name: string, directory = DEFAULT_PROFILES_DIRECTORY) {
+		return WebContext.deleteProfile(name, directory);
+	}
+
 	createTray(options: TrayOptions) {
 		const id = this.generateId();
 		const tray = new Tray(id, options);
@@ -1,21 +1,158 @@
 import type { Pointer } from "bun:ffi";
-import { resolve } from "node:path";
-import { rod_webcontext_create, rod_webcontext_destroy } from "../ffi";
+import { join, resolve } from "node:path";
+import {
+	rod_webcontext_clear_data,
+	rod_webcontext_create,
+	rod_webcontext_delete_cookies,
+	rod_webcontext_delete_profile,
+	rod_webcontext_destroy,
+	rod_webcontext_get_cookies,
+	rod_webcontext_list_profiles,
+	rod_webcontext_open_profile,
+	rod_webcontext_set_proxy,
+} from "../ffi";
+import type {
+	BrowsingDataType,
+	ClearDataOptions,
+	Cookie,
+	Profile,
+	ProxySettings,
+} from "../types";
+import { parseCookie } from "../utilities/cookies";
+import { nativeError } from "../utilities/errors";
 import { encodeString } from "../utilities/strings";
+import type EventLoop from "./eventloop";
+
+let clearDataRequestIndex = 1;
+
+export const DEFAULT_PROFILES_DIRECTORY = "./rod_profiles";
 
 export default class WebContext {
-	readonly path: string;
-	readonly webcontextPtr: Pointer;
+	readonly path: string | null;
+	readonly profile: string | null;
+	readonly webcontextPtr: Pointer | null;
+
+	private eventLoop: EventLoop;
+	private pendingClears: Map<number, (success: boolean) => void>;
+	private onDataCleared: (requestId: number, success: boolean) => void;
 
-	constructor(path: string) {
-		this.path = resolve(path);
+	// A null path creates a context without a data directory, for incognito use.
+	// On Linux it has no native handle, every incognito webview gets an ephemeral
+	// session of its own, so data can't be cleared nor the proxy changed.
+	// With a profile the path is the directory holding the profiles.
+	constructor(
+		eventLoop: EventLoop,
+		path: string | null,
+		profile: string | null = null,
+	) {
+		this.profile = profile;
 
-		const webcontextPtr = rod_webcontext_create(encodeString(this.path));
-		if (!webcontextPtr) throw new Error("Failed to create WebContext");
+		let webcontextPtr: Pointer | null;
+		if (profile === null) {
+			this.path = path === null ? null : resolve(path);
+			webcontextPtr = rod_webcontext_create(encodeString(this.path ?? ""));
+		} else {
+			const directory = resolve(path ?? DEFAULT_PROFILES_DIRECTORY);
+			this.path = join(directory, profile);
+			webcontextPtr = rod_webcontext_open_profile(
+				encodeString(directory),
+				encodeString(profile),
+			);
+		}
+		if (!webcontextPtr && this.path !== null) {
+			throw nativeError("Failed to create WebContext");
+		}
 		this.webcontextPtr = webcontextPtr;
+
+		this.eventLoop = eventLoop;
+		this.pendingClears = new Map();
+		this.onDataCleared = (requestId, success) => {
+			const settle = this.pendingClears.get(requestId);
+			if (!settle) return;
+
+			this.pendingClears.delete(requestId);
+			settle(success);
+		};
+		this.eventLoop.on("webcontext_data_cleared", this.onDataCleared);
+	}
+
+	static listProfiles(directory = DEFAULT_PROFILES_DIRECTORY): Profile[] {
+		const raw = rod_webcontext_list_profiles(encodeString(resolve(directory)));
+		return JSON.parse(raw.toString()).map(
+			(profile: { name: string; path: string; in_use: boolean }) => ({
+				name: profile.name,
+				path: profile.path,
+				inUse: profile.in_use,
+			}),
+		);
+	}
+
+	// Profiles still open by a webview or context are left untouched.
+	static deleteProfile(name: string, directory = DEFAULT_PROFILES_DIRECTORY) {
+		return rod_webcontext_delete_profile(
+			encodeString(resolve(directory)),
+			encodeString(name),
+		);
+	}
+
+	// Only supported on Linux and needs a live webview using this context. The
+	// proxy applies to every webview sharing the context.
+	// Passing null restores the system proxy settings.
+	setProxy(proxy: ProxySettings | null) {
+		const set = rod_webcontext_set_proxy(
+			this.webcontextPtr,
+			encodeString(JSON.stringify(proxy)),
+		);
+		if (!set) throw nativeError("Failed to set proxy");
+	}
+
+	// Needs at least one live webview using this context. `since` can't be
+	// combined with `origins`, and WebView2 can't clear session storage or HSTS.
+	clearData(types: BrowsingDataType[], options: ClearDataOptions = {}) {
+		const requestId = clearDataRequestIndex++;
+		return new Promise<boolean>((settle, reject) => {
+			this.pendingClears.set(requestId, settle);
+			const started = rod_webcontext_clear_data(
+				this.webcontextPtr,
+				requestId,
+				encodeString(JSON.stringify(types)),
+				options.since ? options.since.getTime() / 1000 : 0,
+				encodeString(JSON.stringify(options.origins ?? [])),
+			);
+			if (!started) {
+				this.pendingClears.delete(requestId);
+				reject(nativeError("Failed to clear data"));
+			}
+		});
+	}
+
+	// The whole cookie store, or the cookies sent to `url`. Like clearData it
+	// needs a live webview using this context, the store is only reachable
+	// through one.
+	getCookies(url?: string): Cookie[] {
+		const raw = rod_webcontext_get_cookies(
+			this.webcontextPtr,
+			encodeString(url ?? ""),
+		);
+		const cookies = JSON.parse(raw.toString());
+		if (!cookies) throw nativeError("Failed to get cookies");
+		return cookies.map(parseCookie);
+	}
+
+	// Deletes the whole cookie store, or the cookies sent to `url`.
+	deleteCookies(url?: string) {
+		const deleted = rod_webcontext_delete_cookies(
+			this.webcontextPtr,
+			encodeString(url ?? ""),
+		);
+		if (!deleted) throw nativeError("Failed to delete cookies");
 	}
 
 	destroy() {
+		this.eventLoop.off("webcontext_data_cleared", this.onDataCleared);
+		for (const settle of this.pendingClears.values()) settle(false);
+		this.pendingClears.clear();
+
 		rod_webcontext_destroy(this.webcontextPtr);
 	}
 }
@@ -1,43 +1,215 @@
-import type { Pointer } from "bun:ffi";
+import { type Pointer, ptr } from "bun:ffi";
+import { resolve as resolvePath } from "node:path";
 import {
+	rod_protocol_respond,
+	rod_webview_add_init_script,
+	rod_webview_bring_to_front,
+	rod_webview_can_go_back,
+	rod_webview_can_go_forward,
 	rod_webview_clear_all_browsing_data,
 	rod_webview_close_devtools,
 	rod_webview_create,
+	rod_webview_delete_cookie,
 	rod_webview_destroy,
+	rod_webview_eval,
+	rod_webview_eval_with_callback,
+	rod_webview_focus,
+	rod_webview_focus_parent,
+	rod_webview_get_bounds,
+	rod_webview_get_cookies,
 	rod_webview_get_url,
+	rod_webview_go_back,
+	rod_webview_go_forward,
 	rod_webview_is_devtools_open,
+	rod_webview_load_request,
 	rod_webview_open_devtools,
+	rod_webview_print,
+	rod_webview_print_to_pdf,
 	rod_webview_reload,
+	rod_webview_reparent,
+	rod_webview_resolve_invoke,
+	rod_webview_send_to_back,
+	rod_webview_set_accept_language,
+	rod_webview_set_background_color,
+	rod_webview_set_bounds,
+	rod_webview_set_cookie,
 	rod_webview_set_html,
 	rod_webview_set_url,
+	rod_webview_set_user_agent,
+	rod_webview_set_visible,
+	rod_webview_snapshot,
+	rod_webview_stop,
 	rod_webview_zoom,
+	rod_window_set_visible,
 } from "../ffi";
-import type { WebViewOptions, WindowOptions } from "../types";
+import type {
+	Bounds,
+	Cookie,
+	DownloadCompleted,
+	DownloadHandler,
+	DownloadProgress,
+	DownloadRequest,
+	HistoryState,
+	InvokeHandler,
+	LoadRequest,
+	NavigationHandler,
+	NewWindowRequest,
+	PdfOptions,
+	ProtocolHandler,
+	ProtocolRequest,
+	ProtocolResponse,
+	WebViewOptions,
+	WindowOptions,
+} from "../types";
+import { parseCookie, transformCookie } from "../utilities/cookies";
+import { nativeError } from "../utilities/errors";
 import { transformWebViewOptions } from "../utilities/options";
 import { encodeString } from "../utilities/strings";
 import type EventLoop from "./eventloop";
 import WebContext from "./webcontext";
-import Window from "./window";
+import Window, { type WindowEvents } from "./window";
 
-export default class WebView extends Window {
-	private webcontext: WebContext;
+let evalRequestIndex = 1;
+let snapshotRequestIndex = 1;
+let printRequestIndex = 1;
+
+type PendingEval = {
+	resolve: (value: unknown) => void;
+	reject: (error: Error) => void;
+};
+
+type PendingSnapshot = {
+	resolve: (data: Uint8Array | null) => void;
+	reject: (error: Error) => void;
+};
+
+type PendingPrint = {
+	resolve: () => void;
+	reject: (error: Error) => void;
+};
+
+interface WebViewEvents extends WindowEvents {
+	ipc_message: (message: string, url: string) => void;
+	invoke: (callId: number, command: string, args: unknown) => void;
+	eval_result: (
+		requestId: number,
+		value: unknown,
+		error: string | null,
+	) => void;
+	snapshot: (
+		requestId: number,
+		data: Uint8Array | null,
+		error: string | null,
+	) => void;
+	pdf_printed: (requestId: number, error: string | null) => void;
+	protocol_request: (requestId: number, request: ProtocolRequest) => void;
+	page_load_started: (url: string) => void;
+	// History is only known once a load finishes, before that it describes the
+	// previous page.
+	page_load_finished: (url: string, history: HistoryState) => void;
+	new_window_requested: (request: NewWindowRequest) => void;
+	title_changed: (title: string) => void;
+	download_progress: (progress: DownloadProgress) => void;
+	download_completed: (completed: DownloadCompleted) => void;
+}
+
+export default class WebView extends Window<WebViewEvents> {
+	readonly webcontext: WebContext;
 	protected webviewPtr: Pointer;
+	private invokeHandlers: Map<string, InvokeHandler>;
+	private pendingEvals: Map<number, PendingEval>;
+	private pendingSnapshots: Map<number, PendingSnapshot>;
+	private pendingPrints: Map<number, PendingPrint>;
+	private protocolHandlers: Map<string, ProtocolHandler>;
+	private navigationHandler: NavigationHandler | null;
+	private readonly asksNavigation: boolean;
+	private downloadHandler: DownloadHandler | null;
+	private parent: WebView | null;
+	private children: Set<WebView>;
 	constructor(
 		eventLoop: EventLoop,
 		id: number,
 		options: WebViewOptions & WindowOptions,
 	) {
-		super(eventLoop.eventloopPtr, id, options);
+		super(eventLoop.eventloopPtr, id, options, options.parent?.windowPtr);
 
-		this.webcontext = new WebContext(options.dataDirectory || "./rod_data");
+		if (options.incognito) {
+			this.webcontext = new WebContext(eventLoop, null);
+		} else if (options.profile) {
+			this.webcontext = new WebContext(
+				eventLoop,
+				options.profilesDirectory ?? null,
+				options.profile,
+			);
+		} else {
+			this.webcontext = new WebContext(
+				eventLoop,
+				options.dataDirectory || "./rod_data",
+			);
+		}
 
 		const webviewPtr = rod_webview_create(
 			this.windowPtr,
 			this.webcontext.webcontextPtr,
-			encodeString(JSON.stringify(transformWebViewOptions(options))),
+			encodeString(
+				JSON.stringify({ id, ...transformWebViewOptions(options) }),
+			),
 		);
-		if (!webviewPtr) throw new Error("Failed to create WebView");
+		if (!webviewPtr) {
+			const error = nativeError("Failed to create WebView");
+			this.webcontext.destroy();
+			super.destroy();
+			throw error;
+		}
 		this.webviewPtr = webviewPtr;
+
+		this.children = new Set();
+		this.parent = options.parent ?? null;
+		this.parent?.children.add(this);
+		this.on("destroyed", () => this.parent?.children.delete(this));
+
+		this.invokeHandlers = new Map();
+		this.on("invoke", (callId, command, args) => {
+			this.dispatchInvoke(callId, command, args);
+		});
+
+		this.pendingEvals = new Map();
+		this.on("eval_result", (requestId, value, error) => {
+			const pending = this.pendingEvals.get(requestId);
+			if (!pending) return;
+
+			this.pendingEvals.delete(requestId);
+			if (error !== null) pending.reject(new Error(error));
+			else pending.resolve(value);
+		});
+
+		this.pendingSnapshots = new Map();
+		this.on("snapshot", (requestId, data, error) => {
+			const pending = this.pendingSnapshots.get(requestId);
+			if (!pending) return;
+
+			this.pendingSnapshots.delete(requestId);
+			if (error !== null) pending.reject(new Error(error));
+			else pending.resolve(data);
+		});
+
+		this.pendingPrints = new Map();
+		this.on("pdf_printed", (requestId, error) => {
+			const pending = this.pendingPrints.get(requestId);
+			if (!pending) return;
+
+			this.pendingPrints.delete(requestId);
+			if (error !== null) pending.reject(new Error(error));
+			else pending.resolve();
+		});
+
+		this.navigationHandler = options.navigationHandler ?? null;
+		this.asksNavigation = options.navigationHandler !== undefined;
+		this.downloadHandler = null;
+		this.protocolHandlers = new Map();
+		this.on("protocol_request", (requestId, request) => {
+			this.dispatchProtocolRequest(requestId, request);
+		});
 	}
 
 	get url() {
@@ -49,10 +221,152 @@ export default class WebView extends Window {
 		return rod_webview_is_devtools_open(this.webviewPtr);
 	}
 
+	get canGoBack() {
+		return rod_webview_can_go_back(this.webviewPtr);
+	}
+
+	get canGoForward() {
+		return rod_webview_can_go_forward(this.webviewPtr);
+	}
+
+	get bounds(): Bounds | null {
+		const raw = rod_webview_get_bounds(this.webviewPtr);
+		return JSON.parse(raw.toString());
+	}
+
+	// A webview created without bounds fills its window until it's given some.
+	setBounds(bounds: Bounds) {
+		return rod_webview_set_bounds(
+			this.webviewPtr,
+			encodeString(JSON.stringify(bounds)),
+		);
+	}
+
+	// Shows or hides only the webview, its window stays as it is.
+	setWebViewVisible(visible: boolean) {
+		rod_webview_set_visible(this.webviewPtr, visible);
+	}
+
+	focusWebView() {
+		rod_webview_focus(this.webviewPtr);
+	}
+
+	focusParent() {
+		rod_webview_focus_parent(this.webviewPtr);
+	}
+
+	// Takes "#rrggbb" or "#rrggbbaa".
+	setBackgroundColor(color: string) {
+		return rod_webview_set_background_color(
+			this.webviewPtr,
+			encodeString(color),
+		);
+	}
+
+	bringToFront() {
+		return rod_webview_bring_to_front(this.webviewPtr);
+	}
+
+	sendToBack() {
+		return rod_webview_send_to_back(this.webviewPtr);
+	}
+
+	// Moves the webview, with the webviews inside it, into the window of parent.
+	// A webview with its own window hides that window while it's away, passing
+	// null moves it back.
+	reparent(parent: WebView | null) {
+		for (let ancestor = parent; ancestor; ancestor = ancestor.parent) {
+			if (ancestor === this) {
+				throw new Error("A WebView can't be moved into itself");
+			}
+		}
+
+		const windowPtr = parent ? parent.windowPtr : this.ownedWindowPtr;
+		if (!windowPtr) throw new Error("WebView has no window of its own");
+		if (!this.moveToWindow(windowPtr)) {
+			throw nativeError("Failed to reparent WebView");
+		}
+
+		this.parent?.children.delete(this);
+		parent?.children.add(this);
+		this.parent = parent;
+		if (this.ownedWindowPtr) {
+			rod_window_set_visible(this.ownedWindowPtr, parent === null);
+		}
+	}
+
+	private moveToWindow(windowPtr: Pointer): boolean {
+		if (!rod_webview_reparent(this.webviewPtr, windowPtr)) return false;
+
+		this.windowPtr = windowPtr;
+		for (const child of this.children) child.moveToWindow(windowPtr);
+		return true;
+	}
+
 	setUrl(url: string) {
 		rod_webview_set_url(this.webviewPtr, encodeString(url));
 	}
 
+	// Linux can only send GET requests without a body, other requests throw.
+	loadRequest(request: LoadRequest) {
+		const body =
+			typeof request.body === "string"
+				? Buffer.from(request.body)
+				: request.body && Buffer.from(request.body);
+
+		const loaded = rod_webview_load_request(
+			this.webviewPtr,
+			encodeString(
+				JSON.stringify({
+					url: request.url,
+					method: request.method ?? "GET",
+					headers: request.headers ?? {},
+					body: body ? body.toString("base64") : null,
+				}),
+			),
+		);
+		if (!loaded) throw nativeError(`Failed to load ${request.url}`);
+	}
+
+	setUserAgent(userAgent: string) {
+		return rod_webview_set_user_agent(this.webviewPtr, encodeString(userAgent));
+	}
+
+	// Only supported on Linux, applies to every webview sharing the webcontext.
+	setAcceptLanguage(acceptLanguage: string) {
+		const set = rod_webview_set_accept_language(
+			this.webviewPtr,
+			encodeString(acceptLanguage),
+		);
+		if (!set) throw nativeError("Failed to set accept language");
+	}
+
+	getCookies(url?: string): Cookie[] {
+		const raw = rod_webview_get_cookies(
+			this.webviewPtr,
+			encodeString(url ?? ""),
+		);
+		return JSON.parse(raw.toString()).map(parseCookie);
+	}
+
+	getCookie(name: string, url?: string) {
+		return this.getCookies(url).find((cookie) => cookie.name === name) ?? null;
+	}
+
+	setCookie(cookie: Cookie) {
+		return rod_webview_set_cookie(
+			this.webviewPtr,
+			encodeString(JSON.stringify(transformCookie(cookie))),
+		);
+	}
+
+	deleteCookie(cookie: Cookie) {
+		return rod_webview_delete_cookie(
+			this.webviewPtr,
+			encodeString(JSON.stringify(transformCookie(cookie))),
+		);
+	}
+
 	setHtml(html: string) {
 		rod_webview_set_html(this.webviewPtr, encodeString(html));
 	}
@@ -61,10 +375,217 @@ export default class WebView extends Window {
 		rod_webview_reload(this.webviewPtr);
 	}
 
+	goBack() {
+		rod_webview_go_back(this.webviewPtr);
+	}
+
+	goForward() {
+		rod_webview_go_forward(this.webviewPtr);
+	}
+
+	stop() {
+		rod_webview_stop(this.webviewPtr);
+	}
+
 	clearAllBrowsingData() {
 		rod_webview_clear_all_browsing_data(this.webviewPtr);
 	}
 
+	evaluate(script: string) {
+		rod_webview_eval(this.webviewPtr, encodeString(script));
+	}
+
+	// Promises are awaited, the result has to be JSON serializable.
+	evaluateWithResult(script: string) {
+		const requestId = evalRequestIndex++;
+		return new Promise<unknown>((resolve, reject) => {
+			this.pendingEvals.set(requestId, { resolve, reject });
+			const started = rod_webview_eval_with_callback(
+				this.webviewPtr,
+				requestId,
+				encodeString(script),
+			);
+			if (started) return;
+
+			this.pendingEvals.delete(requestId);
+			reject(new Error("Failed to evaluate script"));
+		});
+	}
+
+	// Renders the visible area, or the whole document, to PNG bytes.
+	async snapshot(fullDocument = false) {
+		const data = await this.captureSnapshot({ full_document: fullDocument });
+		return data ?? new Uint8Array();
+	}
+
+	async saveSnapshot(path: string, fullDocument = false) {
+		await this.captureSnapshot({
+			full_document: fullDocument,
+			path: resolvePath(path),
+		});
+	}
+
+	private captureSnapshot(options: { full_document: boolean; path?: string }) {
+		const requestId = snapshotRequestIndex++;
+		return new Promise<Uint8Array | null>((resolve, reject) => {
+			this.pendingSnapshots.set(requestId, { resolve, reject });
+			const started = rod_webview_snapshot(
+				this.webviewPtr,
+				requestId,
+				encodeString(JSON.stringify(options)),
+			);
+			if (started) return;
+
+			this.pendingSnapshots.delete(requestId);
+			reject(new Error("Failed to capture snapshot"));
+		});
+	}
+
+	// Opens the native print dialog.
+	print() {
+		rod_webview_print(this.webviewPtr);
+	}
+
+	printToPdf(path: string, options: PdfOptions = {}) {
+		const requestId = printRequestIndex++;
+		return new Promise<void>((resolve, reject) => {
+			this.pendingPrints.set(requestId, { resolve, reject });
+			const started = rod_webview_print_to_pdf(
+				this.webviewPtr,
+				requestId,
+				encodeString(
+					JSON.stringify({
+						path: resolvePath(path),
+						page_size: options.pageSize,
+						margins: options.margins,
+						landscape: options.landscape,
+					}),
+				),
+			);
+			if (started) return;
+
+			this.pendingPrints.delete(requestId);
+			reject(new Error("Failed to print to PDF"));
+		});
+	}
+
+	addInitializationScript(script: string, mainFrameOnly = false) {
+		rod_webview_add_init_script(
+			this.webviewPtr,
+			encodeString(script),
+			mainFrameOnly,
+		);
+	}
+
+	handle(command: string, handler: InvokeHandler) {
+		this.invokeHandlers.set(command, handler);
+	}
+
+	removeHandler(command: string) {
+		this.invokeHandlers.delete(command);
+	}
+
+	private async dispatchInvoke(
+		callId: number,
+		command: string,
+		args: unknown,
+	) {
+		let payload: unknown;
+		let isError = false;
+		try {
+			const handler = this.invokeHandlers.get(command);
+			if (!handler) throw new Error(`No handler registered for "${command}"`);
+
+			payload = (await handler(args)) ?? null;
+		} catch (error) {
+			const message = error instanceof Error ? error.message : String(error);
+			payload = { message };
+			isError = true;
+		}
+		this.resolveInvoke(callId, payload, isError);
+	}
+
+	// The page's call is rejected when the payload can't be sent, the error is
+	// thrown here as well.
+	private resolveInvoke(callId: number, payload: unknown, isError: boolean) {
+		if (!this.webviewPtr) return;
+
+		let json: string;
+		try {
+			json = JSON.stringify(payload);
+		} catch (error) {
+			const message = error instanceof Error ? error.message : String(error);
+			json = JSON.stringify({ message });
+			isError = true;
+		}
+
+		const resolved = rod_webview_resolve_invoke(
+			this.webviewPtr,
+			callId,
+			encodeString(json),
+			isError,
+		);
+		if (!resolved) throw nativeError("Failed to resolve invoke");
+	}
+
+	handleProtocol(protocol: string, handler: ProtocolHandler) {
+		this.protocolHandlers.set(protocol, handler);
+	}
+
+	removeProtocolHandler(protocol: string) {
+		this.protocolHandlers.delete(protocol);
+	}
+
+	private async dispatchProtocolRequest(
+		requestId: number,
+		request: ProtocolRequest,
+	) {
+		try {
+			const handler = this.protocolHandlers.get(request.protocol);
+			if (!handler) return this.respondProtocol(requestId, { status: 404 });
+
+			this.respondProtocol(requestId, await handler(request));
+		} catch (error) {
+			const message = error instanceof Error ? error.message : String(error);
+			this.respondProtocol(requestId, { status: 500, body: message });
+		}
+	}
+
+	private respondProtocol(requestId: number, response: ProtocolResponse) {
+		const body =
+			typeof response.body === "string"
+				? new TextEncoder().encode(response.body)
+				: (response.body ?? new Uint8Array());
+
+		rod_protocol_respond(
+			requestId,
+			response.status ?? 200,
+			encodeString(JSON.stringify(response.headers ?? {})),
+			body.byteLength > 0 ? ptr(body) : null,
+			body.byteLength,
+		);
+	}
+
+	// Passing null allows every navigation the navigation policy allows.
+	setNavigationHandler(handler: NavigationHandler | null) {
+		if (!this.asksNavigation) {
+			throw new Error("WebView was created without a navigationHandler");
+		}
+		this.navigationHandler = handler;
+	}
+
+	decideNavigation(url: string) {
+		return this.navigationHandler ? this.navigationHandler(url) : true;
+	}
+
+	setDownloadHandler(handler: DownloadHandler | null) {
+		this.downloadHandler = handler;
+	}
+
+	decideDownload(request: DownloadRequest) {
+		return this.downloadHandler ? this.downloadHandler(request) : {};
+	}
+
 	openDevtools() {
 		rod_webview_open_devtools(this.webviewPtr);
 	}
@@ -80,8 +601,28 @@ export default class WebView extends Window {
 	override destroy() {
 		if (!this.webviewPtr) return;
 
+		// Child webviews live in this webview's window, they go first.
+		for (const child of this.children) child.destroy();
+
 		rod_webview_destroy(this.webviewPtr);
 		this.webviewPtr = null as unknown as Pointer;
+		this.invokeHandlers.clear();
+		this.protocolHandlers.clear();
+
+		for (const pending of this.pendingEvals.values()) {
+			pending.reject(new Error("WebView was destroyed"));
+		}
+		this.pendingEvals.clear();
+
+		for (const pending of this.pendingSnapshots.values()) {
+			pending.reject(new Error("WebView was destroyed"));
+		}
+		this.pendingSnapshots.clear();
+
+		for (const pending of this.pendingPrints.values()) {
+			pending.reject(new Error("WebView was destroyed"));
+		}
+		this.pendingPrints.clear();
 
 		this.webcontext.destroy();
 		super.destroy();
@@ -1,5 +1,5 @@
 import type { Pointer } from "bun:ffi";
-import { TypedEmitter } from "tiny-typed-emitter";
+import { type ListenerSignature, TypedEmitter } from "tiny-typed-emitter";
 import {
 	rod_window_create,
 	rod_window_destroy,
@@ -44,7 +44,7 @@ import type { Position, ProgressState, Size, WindowOptions } from "../types";
 import { transformWindowOptions } from "../utilities/options";
 import { encodeString } from "../utilities/strings";
 
-interface WindowEvents {
+export interface WindowEvents {
 	close_requested: () => void;
 	focused: (focused: boolean) => void;
 	moved: (position: Position) => void;
@@ -52,14 +52,32 @@ interface WindowEvents {
 	destroyed: () => void;
 }
 
-export default class Window extends TypedEmitter<WindowEvents> {
+export default class Window<
+	Events extends ListenerSignature<Events> = WindowEvents,
+> extends TypedEmitter<Events> {
 	id: number;
 	protected windowPtr: Pointer;
-	constructor(eventLoop: Pointer, id: number, options: WindowOptions) {
+	// The window this instance created and destroys, windowPtr can point to
+	// another window while a webview is moved.
+	protected ownedWindowPtr: Pointer | null;
+	constructor(
+		eventLoop: Pointer,
+		id: number,
+		options: WindowOptions,
+		parentWindowPtr: Pointer | null = null,
+	) {
 		super();
 
 		this.id = id;
 
+		// Child webviews are placed in the window of their parent, which stays
+		// responsible for destroying it.
+		if (parentWindowPtr) {
+			this.windowPtr = parentWindowPtr;
+			this.ownedWindowPtr = null;
+			return;
+		}
+
 		const windowPtr = rod_window_create(
 			eventLoop,
 			id,
@@ -67,135 +85,146 @@ export default class Window extends TypedEmitter<WindowEvents> {
 		);
 		if (!windowPtr) throw new Error("Failed to create Window");
 		this.windowPtr = windowPtr;
+		this.ownedWindowPtr = windowPtr;
+	}
+
+	// Window methods act on the window this instance created. Child webviews
+	// live in their parent's window and have none, they throw instead of
+	// changing the parent's window.
+	protected get ownWindowPtr() {
+		if (!this.ownedWindowPtr) {
+			throw new Error("This instance has no window of its own");
+		}
+		return this.ownedWindowPtr;
 	}
 
 	get title() {
-		const title = rod_window_get_title(this.windowPtr);
+		const title = rod_window_get_title(this.ownWindowPtr);
 		return title.toString();
 	}
 
 	get size() {
-		const rawSize = rod_window_get_size(this.windowPtr);
+		const rawSize = rod_window_get_size(this.ownWindowPtr);
 		return JSON.parse(rawSize.toString()) as Size;
 	}
 
 	get position() {
-		const rawPosition = rod_window_get_position(this.windowPtr);
+		const rawPosition = rod_window_get_position(this.ownWindowPtr);
 		return JSON.parse(rawPosition.toString()) as Position;
 	}
 
 	get isAlwaysOnTop() {
-		return rod_window_get_always_on_top(this.windowPtr);
+		return rod_window_get_always_on_top(this.ownWindowPtr);
 	}
 	get isClosable() {
-		return rod_window_get_closable(this.windowPtr);
+		return rod_window_get_closable(this.ownWindowPtr);
 	}
 	get isDecorated() {
-		return rod_window_get_decorated(this.windowPtr);
+		return rod_window_get_decorated(this.ownWindowPtr);
 	}
 	get isFocused() {
-		return rod_window_get_focused(this.windowPtr);
+		return rod_window_get_focused(this.ownWindowPtr);
 	}
 	get isMaximizable() {
-		return rod_window_get_maximizable(this.windowPtr);
+		return rod_window_get_maximizable(this.ownWindowPtr);
 	}
 	get isMaximized() {
-		return rod_window_get_maximized(this.windowPtr);
+		return rod_window_get_maximized(this.ownWindowPtr);
 	}
 	get isMinimizable() {
-		return rod_window_get_minimizable(this.windowPtr);
+		return rod_window_get_minimizable(this.ownWindowPtr);
 	}
 	get isMinimized() {
-		return rod_window_get_minimized(this.windowPtr);
+		return rod_window_get_minimized(this.ownWindowPtr);
 	}
 	get isResizable() {
-		return rod_window_get_resizable(this.windowPtr);
+		return rod_window_get_resizable(this.ownWindowPtr);
 	}
 	get isVisible() {
-		return rod_window_get_visible(this.windowPtr);
+		return rod_window_get_visible(this.ownWindowPtr);
 	}
 
 	setAlwaysOnBottom(alwaysOnBottom: boolean) {
-		rod_window_set_always_on_bottom(this.windowPtr, alwaysOnBottom);
+		rod_window_set_always_on_bottom(this.ownWindowPtr, alwaysOnBottom);
 	}
 
 	setAlwaysOnTop(alwaysOnTop: boolean) {
-		rod_window_set_always_on_top(this.windowPtr, alwaysOnTop);
+		rod_window_set_always_on_top(this.ownWindowPtr, alwaysOnTop);
 	}
 
 	setClosable(closable: boolean) {
-		rod_window_set_closable(this.windowPtr, closable);
+		rod_window_set_closable(this.ownWindowPtr, closable);
 	}
 
 	setContentProtection(contentProtection: boolean) {
-		rod_window_set_content_protection(this.windowPtr, contentProtection);
+		rod_window_set_content_protection(this.ownWindowPtr, contentProtection);
 	}
 
 	setDecorations(decorations: boolean) {
-		rod_window_set_decorations(this.windowPtr, decorations);
+		rod_window_set_decorations(this.ownWindowPtr, decorations);
 	}
 
 	setFocus() {
-		rod_window_set_focus(this.windowPtr);
+		rod_window_set_focus(this.ownWindowPtr);
 	}
 
 	setFocusable(focusable: boolean) {
-		rod_window_set_focusable(this.windowPtr, focusable);
+		rod_window_set_focusable(this.ownWindowPtr, focusable);
 	}
 
 	setFullscreen(fullscreen: boolean) {
-		rod_window_set_fullscreen(this.windowPtr, fullscreen);
+		rod_window_set_fullscreen(this.ownWindowPtr, fullscreen);
 	}
 
 	setIgnoreCursorEvents(ignore: boolean) {
-		rod_window_set_ignore_cursor_events(this.windowPtr, ignore);
+		rod_window_set_ignore_cursor_events(this.ownWindowPtr, ignore);
 	}
 
 	setSize(size: Size) {
-		rod_window_set_size(this.windowPtr, encodeString(JSON.stringify(size)));
+		rod_window_set_size(this.ownWindowPtr, encodeString(JSON.stringify(size)));
 	}
 
 	setMaximumSize(size: Size | null) {
 		if (size === null) {
-			rod_window_set_maximum_size(this.windowPtr, encodeString(""));
+			rod_window_set_maximum_size(this.ownWindowPtr, encodeString(""));
 		} else {
 			rod_window_set_maximum_size(
-				this.windowPtr,
+				this.ownWindowPtr,
 				encodeString(JSON.stringify(size)),
 			);
 		}
 	}
 
 	setMaximizable(maximizable: boolean) {
-		rod_window_set_maximizable(this.windowPtr, maximizable);
+		rod_window_set_maximizable(this.ownWindowPtr, maximizable);
 	}
 
 	setMaximized(maximized: boolean) {
-		rod_window_set_maximized(this.windowPtr, maximized);
+		rod_window_set_maximized(this.ownWindowPtr, maximized);
 	}
 
 	setMinimumSize(size: Size | null) {
 		if (size === null) {
-			rod_window_set_minimum_size(this.windowPtr, encodeString(""));
+			rod_window_set_minimum_size(this.ownWindowPtr, encodeString(""));
 		} else {
 			rod_window_set_minimum_size(
-				this.windowPtr,
+				this.ownWindowPtr,
 				encodeString(JSON.stringify(size)),
 			);
 		}
 	}
 
 	setMinimizable(minimizable: boolean) {
-		rod_window_set_minimizable(this.windowPtr, minimizable);
+		rod_window_set_minimizable(this.ownWindowPtr, minimizable);
 	}
 
 	setMinimized(minimized: boolean) {
-		rod_window_set_minimized(this.windowPtr, minimized);
+		rod_window_set_minimized(this.ownWindowPtr, minimized);
 	}
 
 	setPosition(position: Position) {
 		rod_window_set_position(
-			this.windowPtr,
+			this.ownWindowPtr,
 			encodeString(JSON.stringify(position)),
 		);
 	}
@@ -203,37 +232,38 @@ export default class Window extends TypedEmitter<WindowEvents> {
 	setProgressBar(state: ProgressState, progress: number) {
 		const payload = { state: state as number, progress };
 		rod_window_set_progress_bar(
-			this.windowPtr,
+			this.ownWindowPtr,
 			encodeString(JSON.stringify(payload)),
 		);
 	}
 
 	setResizable(resizable: boolean) {
-		rod_window_set_resizable(this.windowPtr, resizable);
+		rod_window_set_resizable(this.ownWindowPtr, resizable);
 	}
 
 	setTitle(title: string) {
-		rod_window_set_title(this.windowPtr, encodeString(title));
+		rod_window_set_title(this.ownWindowPtr, encodeString(title));
 	}
 
 	setVisible(visible: boolean) {
-		rod_window_set_visible(this.windowPtr, visible);
+		rod_window_set_visible(this.ownWindowPtr, visible);
 	}
 
 	setVisibleOnAllWorkspaces(visible: boolean) {
-		rod_window_set_visible_on_all_workspaces(this.windowPtr, visible);
+		rod_window_set_visible_on_all_workspaces(this.ownWindowPtr, visible);
 	}
 
 	startDrag() {
-		rod_window_start_drag(this.windowPtr);
+		rod_window_start_drag(this.ownWindowPtr);
 	}
 
 	destroy() {
 		if (!this.windowPtr) return;
 
-		this.emit("destroyed");
+		(this as unknown as TypedEmitter<WindowEvents>).emit("destroyed");
 
-		rod_window_destroy(this.windowPtr);
+		if (this.ownedWindowPtr) rod_window_destroy(this.ownedWindowPtr);
 		this.windowPtr = null as unknown as Pointer;
+		this.ownedWindowPtr = null;
 	}
 }
@@ -20,6 +20,7 @@ const {
 
 		// event loop actions
 		rod_event_loop_poll,
+		rod_set_decision_callback,
 
 		// window
 		rod_window_create,
@@ -70,6 +71,13 @@ const {
 		// webcontext
 		rod_webcontext_create,
 		rod_webcontext_destroy,
+		rod_webcontext_open_profile,
+		rod_webcontext_list_profiles,
+		rod_webcontext_delete_profile,
+		rod_webcontext_set_proxy,
+		rod_webcontext_clear_data,
+		rod_webcontext_get_cookies,
+		rod_webcontext_delete_cookies,
 
 		// webview
 		rod_webview_create,
@@ -78,17 +86,50 @@ const {
 		// webview getters
 		rod_webview_get_url,
 		rod_webview_is_devtools_open,
+		rod_webview_can_go_back,
+		rod_webview_can_go_forward,
+		rod_webview_get_cookies,
+		rod_webview_get_bounds,
 
 		// webview setters
 		rod_webview_set_url,
+		rod_webview_load_request,
+		rod_webview_set_cookie,
+		rod_webview_delete_cookie,
+		rod_webview_set_user_agent,
+		rod_webview_set_accept_language,
 		rod_webview_set_html,
+		rod_webview_set_bounds,
+		rod_webview_set_visible,
+		rod_webview_set_background_color,
 		rod_webview_zoom,
 
 		// webview actions
 		rod_webview_open_devtools,
 		rod_webview_close_devtools,
 		rod_webview_reload,
+		rod_webview_go_back,
+		rod_webview_go_forward,
+		rod_webview_stop,
 		rod_webview_clear_all_browsing_data,
+		rod_webview_resolve_invoke,
+		rod_webview_eval,
+		rod_webview_eval_with_callback,
+		rod_webview_add_init_script,
+		rod_webview_bring_to_front,
+		rod_webview_send_to_back,
+		rod_webview_reparent,
+		rod_webview_focus,
+		rod_webview_focus_parent,
+		rod_webview_snapshot,
+		rod_webview_print,
+		rod_webview_print_to_pdf,
+
+		// protocol
+		rod_protocol_respond,
+
+		// errors
+		rod_take_last_error,
 
 		// tray
 		rod_tray_create,
@@ -108,6 +149,10 @@ const {
 		args: [FFIType.ptr, FFIType.function],
 		returns: FFIType.void,
 	},
+	rod_set_decision_callback: {
+		args: [FFIType.function],
+		returns: FFIType.void,
+	},
 
 	// window
 	rod_window_create: {
@@ -278,6 +323,40 @@ const {
 		args: [FFIType.ptr],
 		returns: FFIType.void,
 	},
+	rod_webcontext_open_profile: {
+		args: [FFIType.cstring, FFIType.cstring],
+		returns: FFIType.ptr,
+	},
+	rod_webcontext_list_profiles: {
+		args: [FFIType.cstring],
+		returns: FFIType.cstring,
+	},
+	rod_webcontext_delete_profile: {
+		args: [FFIType.cstring, FFIType.cstring],
+		returns: FFIType.bool,
+	},
+	rod_webcontext_set_proxy: {
+		args: [FFIType.ptr, FFIType.cstring],
+		returns: FFIType.bool,
+	},
+	rod_webcontext_clear_data: {
+		args: [
+			FFIType.ptr,
+			FFIType.u32,
+			FFIType.cstring,
+			FFIType.f64,
+			FFIType.cstring,
+		],
+		returns: FFIType.bool,
+	},
+	rod_webcontext_get_cookies: {
+		args: [FFIType.ptr, FFIType.cstring],
+		returns: FFIType.cstring,
+	},
+	rod_webcontext_delete_cookies: {
+		args: [FFIType.ptr, FFIType.cstring],
+		returns: FFIType.bool,
+	},
 
 	// webview
 	rod_webview_create: {
@@ -316,14 +395,134 @@ const {
 		args: [FFIType.ptr],
 		returns: FFIType.void,
 	},
+	rod_webview_get_cookies: {
+		args: [FFIType.ptr, FFIType.cstring],
+		returns: FFIType.cstring,
+	},
+	rod_webview_set_cookie: {
+		args: [FFIType.ptr, FFIType.cstring],
+		returns: FFIType.bool,
+	},
+	rod_webview_delete_cookie: {
+		args: [FFIType.ptr, FFIType.cstring],
+		returns: FFIType.bool,
+	},
+	rod_webview_set_user_agent: {
+		args: [FFIType.ptr, FFIType.cstring],
+		returns: FFIType.bool,
+	},
+	rod_webview_set_accept_language: {
+		args: [FFIType.ptr, FFIType.cstring],
+		returns: FFIType.bool,
+	},
+	rod_webview_load_request: {
+		args: [FFIType.ptr, FFIType.cstring],
+		returns: FFIType.bool,
+	},
+	rod_webview_go_back: {
+		args: [FFIType.ptr],
+		returns: FFIType.void,
+	},
+	rod_webview_go_forward: {
+		args: [FFIType.ptr],
+		returns: FFIType.void,
+	},
+	rod_webview_can_go_back: {
+		args: [FFIType.ptr],
+		returns: FFIType.bool,
+	},
+	rod_webview_can_go_forward: {
+		args: [FFIType.ptr],
+		returns: FFIType.bool,
+	},
+	rod_webview_stop: {
+		args: [FFIType.ptr],
+		returns: FFIType.void,
+	},
 	rod_webview_set_html: {
 		args: [FFIType.ptr, FFIType.cstring],
 		returns: FFIType.void,
 	},
+	rod_webview_set_bounds: {
+		args: [FFIType.ptr, FFIType.cstring],
+		returns: FFIType.bool,
+	},
+	rod_webview_get_bounds: {
+		args: [FFIType.ptr],
+		returns: FFIType.cstring,
+	},
+	rod_webview_bring_to_front: {
+		args: [FFIType.ptr],
+		returns: FFIType.bool,
+	},
+	rod_webview_send_to_back: {
+		args: [FFIType.ptr],
+		returns: FFIType.bool,
+	},
+	rod_webview_reparent: {
+		args: [FFIType.ptr, FFIType.ptr],
+		returns: FFIType.bool,
+	},
+	rod_webview_set_visible: {
+		args: [FFIType.ptr, FFIType.bool],
+		returns: FFIType.void,
+	},
+	rod_webview_focus: {
+		args: [FFIType.ptr],
+		returns: FFIType.void,
+	},
+	rod_webview_focus_parent: {
+		args: [FFIType.ptr],
+		returns: FFIType.void,
+	},
+	rod_webview_set_background_color: {
+		args: [FFIType.ptr, FFIType.cstring],
+		returns: FFIType.bool,
+	},
+	rod_webview_snapshot: {
+		args: [FFIType.ptr, FFIType.u32, FFIType.cstring],
+		returns: FFIType.bool,
+	},
+	rod_webview_print: {
+		args: [FFIType.ptr],
+		returns: FFIType.void,
+	},
+	rod_webview_print_to_pdf: {
+		args: [FFIType.ptr, FFIType.u32, FFIType.cstring],
+		returns: FFIType.bool,
+	},
 	rod_webview_clear_all_browsing_data: {
 		args: [FFIType.ptr],
 		returns: FFIType.void,
 	},
+	rod_webview_resolve_invoke: {
+		args: [FFIType.ptr, FFIType.u32, FFIType.cstring, FFIType.bool],
+		returns: FFIType.bool,
+	},
+	rod_webview_eval: {
+		args: [FFIType.ptr, FFIType.cstring],
+		returns: FFIType.void,
+	},
+	rod_webview_eval_with_callback: {
+		args: [FFIType.ptr, FFIType.u32, FFIType.cstring],
+		returns: FFIType.bool,
+	},
+	rod_webview_add_init_script: {
+		args: [FFIType.ptr, FFIType.cstring, FFIType.bool],
+		returns: FFIType.void,
+	},
+
+	// protocol
+	rod_protocol_respond: {
+		args: [FFIType.u32, FFIType.u16, FFIType.cstring, FFIType.ptr, FFIType.u64],
+		returns: FFIType.void,
+	},
+
+	// errors
+	rod_take_last_error: {
+		args: [],
+		returns: FFIType.cstring,
+	},
 
 	// tray
 	rod_tray_create: {
@@ -342,6 +541,7 @@ export {
 	rod_event_loop_destroy,
 	// event loop actions
 	rod_event_loop_poll,
+	rod_set_decision_callback,
 	// window
 	rod_window_create,
 	rod_window_destroy,
@@ -387,21 +587,59 @@ export {
 	// webcontext
 	rod_webcontext_create,
 	rod_webcontext_destroy,
+	rod_webcontext_open_profile,
+	rod_webcontext_list_profiles,
+	rod_webcontext_delete_profile,
+	rod_webcontext_set_proxy,
+	rod_webcontext_clear_data,
+	rod_webcontext_get_cookies,
+	rod_webcontext_delete_cookies,
 	// webview
 	rod_webview_create,
 	rod_webview_destroy,
 	// webview getters
 	rod_webview_get_url,
 	rod_webview_is_devtools_open,
+	rod_webview_can_go_back,
+	rod_webview_can_go_forward,
+	rod_webview_get_cookies,
+	rod_webview_get_bounds,
 	// webview setters
 	rod_webview_set_url,
+	rod_webview_load_request,
+	rod_webview_set_cookie,
+	rod_webview_delete_cookie,
+	rod_webview_set_user_agent,
+	rod_webview_set_accept_language,
 	rod_webview_set_html,
+	rod_webview_set_bounds,
+	rod_webview_set_visible,
+	rod_webview_set_background_color,
 	rod_webview_zoom,
 	// webview actions
 	rod_webview_open_devtools,
 	rod_webview_close_devtools,
 	rod_webview_reload,
+	rod_webview_go_back,
+	rod_webview_go_forward,
+	rod_webview_stop,
 	rod_webview_clear_all_browsing_data,
+	rod_webview_resolve_invoke,
+	rod_webview_eval,
+	rod_webview_eval_with_callback,
+	rod_webview_add_init_script,
+	rod_webview_bring_to_front,
+	rod_webview_send_to_back,
+	rod_webview_reparent,
+	rod_webview_focus,
+	rod_webview_focus_parent,
+	rod_webview_snapshot,
+	rod_webview_print,
+	rod_webview_print_to_pdf,
+	// protocol
+	rod_protocol_respond,
+	// errors
+	rod_take_last_error,
 	// tray
 	rod_tray_create,
 	rod_tray_destroy,
@@ -1,5 +1,6 @@
 import Rod from "./classes/rod";
 import type Tray from "./classes/tray";
+import type WebContext from "./classes/webcontext";
 import type WebView from "./classes/webview";
 import type Window from "./classes/window";
 export default Rod;
@@ -8,3 +9,4 @@ export * from "./types";
 export type { WebView };
 export type { Window };
 export type { Tray };
+export type { WebContext };
@@ -1,15 +1,191 @@
+import type WebView from "./classes/webview";
+
 export type WebViewOptions = {
 	autoplay?: boolean;
 	backForwardNavigationGestures?: boolean;
 	devTools?: boolean;
 	hotkeysZoom?: boolean;
 	transparent?: boolean;
+	backgroundColor?: string;
 	html?: string;
 	url?: string;
+	headers?: Record<string, string>;
+	// Applies to the whole webcontext, creating a webview fails when its profile
+	// is already in use with a different proxy.
+	proxy?: ProxySettings;
+	userAgent?: string;
+	// Only supported on Linux, applies to every webview sharing the webcontext.
+	// Creation fails when the webcontext is in use with different languages.
+	acceptLanguage?: string;
 	incognito?: boolean;
 	dataDirectory?: string;
+	profile?: string;
+	profilesDirectory?: string;
+	initializationScripts?: InitializationScript[];
+	customProtocols?: string[];
+	assetDirectories?: AssetDirectory[];
+	assetArchives?: AssetArchive[];
+	navigation?: NavigationPolicy;
+	// Asked before every navigation. Webviews created without one never wait on
+	// Bun to navigate, and can't be given one later.
+	navigationHandler?: NavigationHandler;
+	newWindowBehavior?: NewWindowBehavior;
+	syncWindowTitle?: boolean;
+	bounds?: Bounds;
+	parent?: WebView;
+};
+
+export type NavigationPolicy = {
+	allow?: string[];
+	deny?: string[];
+};
+
+export type NavigationHandler = (url: string) => boolean;
+
+export type DownloadRequest = {
+	url: string;
+	suggestedFilename: string | null;
+	path: string;
+};
+
+export type DownloadDecision = {
+	allow?: boolean;
+	path?: string;
+};
+
+export type DownloadHandler = (request: DownloadRequest) => DownloadDecision;
+
+export type DownloadProgress = {
+	url: string;
+	receivedBytes: number;
+	totalBytes: number;
+	progress: number;
+};
+
+export type DownloadCompleted = {
+	url: string;
+	path: string | null;
+	success: boolean;
+};
+
+export type LoadRequest = {
+	url: string;
+	method?: string;
+	headers?: Record<string, string>;
+	body?: string | Uint8Array;
+};
+
+export type CookieSameSite = "strict" | "lax" | "none";
+
+export type Cookie = {
+	name: string;
+	value: string;
+	domain?: string;
+	path?: string;
+	expires?: Date;
+	httpOnly?: boolean;
+	secure?: boolean;
+	sameSite?: CookieSameSite;
+};
+
+export type ProxySettings = {
+	type?: "http" | "socks5";
+	host: string;
+	port: number;
+};
+
+export type Profile = {
+	name: string;
+	path: string;
+	inUse: boolean;
+};
+
+export type BrowsingDataType =
+	| "all"
+	| "cache"
+	| "cookies"
+	| "local_storage"
+	| "session_storage"
+	| "indexeddb"
+	| "websql"
+	| "service_workers"
+	| "hsts";
+
+export type ClearDataOptions = {
+	since?: Date;
+	origins?: string[];
+};
+
+export type HistoryState = {
+	canGoBack: boolean;
+	canGoForward: boolean;
+};
+
+// Sizes and margins are in millimeters.
+export type PdfPageSize = "a3" | "a4" | "a5" | "letter" | "legal" | Size;
+
+export type PdfMargins = {
+	top?: number;
+	right?: number;
+	bottom?: number;
+	left?: number;
+};
+
+export type PdfOptions = {
+	pageSize?: PdfPageSize;
+	margins?: PdfMargins;
+	landscape?: boolean;
+};
+
+export type NewWindowBehavior = "deny" | "browser" | "event";
+
+export type NewWindowRequest = {
+	url: string;
+	size: Size | null;
+	position: Position | null;
+};
+
+export type AssetDirectory = {
+	protocol: string;
+	directory: string;
+	host?: string;
+	spaFallback?: boolean;
+};
+
+export type AssetArchive = {
+	protocol: string;
+	archive: string;
+	host?: string;
+	spaFallback?: boolean;
+	integrity?: string;
+};
+
+export type InitializationScript = {
+	script: string;
+	mainFrameOnly?: boolean;
+};
+
+export type InvokeHandler = (args: unknown) => unknown;
+
+export type ProtocolRequest = {
+	protocol: string;
+	method: string;
+	url: string;
+	headers: Record<string, string>;
+	body: Uint8Array;
+};
+
+export type ProtocolResponse = {
+	status?: number;
+	// Repeated headers like Set-Cookie are given as an array.
+	headers?: Record<string, string | string[]>;
+	body?: string | Uint8Array;
 };
 
+export type ProtocolHandler = (
+	request: ProtocolRequest,
+) => ProtocolResponse | Promise<ProtocolResponse>;
+
 export type WindowOptions = {
 	alwaysOnBottom?: boolean;
 	alwaysOnTop?: boolean;
@@ -49,6 +225,8 @@ export type Position = {
 	y: number;
 };
 
+export type Bounds = Position & Size;
+
 export enum ProgressState {
 	Normal = 0,
 	Intermediate = 1,
@@ -0,0 +1,40 @@
+import type { Cookie, CookieSameSite } from "../types";
+
+type RawCookie = {
+	name: string;
+	value: string;
+	domain: string | null;
+	path: string | null;
+	expires: number | null;
+	http_only: boolean | null;
+	secure: boolean | null;
+	same_site: CookieSameSite | null;
+};
+
+export function transformCookie(cookie: Cookie) {
+	return {
+		name: cookie.name,
+		value: cookie.value,
+		domain: cookie.domain,
+		path: cookie.path,
+		expires: cookie.expires
+			? Math.floor(cookie.expires.getTime() / 1000)
+			: undefined,
+		http_only: cookie.httpOnly,
+		secure: cookie.secure,
+		same_site: cookie.sameSite,
+	};
+}
+
+export function parseCookie(raw: RawCookie): Cookie {
+	return {
+		name: raw.name,
+		value: raw.value,
+		domain: raw.domain ?? undefined,
+		path: raw.path ?? undefined,
+		expires: raw.expires !== null ? new Date(raw.expires * 1000) : undefined,
+		httpOnly: raw.http_only ?? undefined,
+		secure: raw.secure ?? undefined,
+		sameSite: raw.same_site ?? undefined,
+	};
+}
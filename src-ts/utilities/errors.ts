@@ -0,0 +1,8 @@
+import { rod_take_last_error } from "../ffi";
+
+// Builds an error for a failed native call, with the reason recorded by the
+// native side when there is one.
+export function nativeError(message: string) {
+	const reason = rod_take_last_error().toString();
+	return new Error(reason ? `${message}: ${reason}` : message);
+}
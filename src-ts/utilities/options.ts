@@ -8,9 +8,37 @@ export function transformWebViewOptions(options: WebViewOptions) {
 		dev_tools: options.devTools,
 		hotkeys_zoom: options.hotkeysZoom,
 		transparent: options.transparent,
+		background_color: options.backgroundColor,
 		html: options.html,
 		url: options.url,
 		incognito: options.incognito,
+		initialization_scripts: options.initializationScripts?.map((script) => ({
+			script: script.script,
+			main_frame_only: script.mainFrameOnly,
+		})),
+		custom_protocols: options.customProtocols,
+		asset_directories: options.assetDirectories?.map((assetDirectory) => ({
+			protocol: assetDirectory.protocol,
+			directory: resolve(assetDirectory.directory),
+			host: assetDirectory.host,
+			spa_fallback: assetDirectory.spaFallback,
+		})),
+		asset_archives: options.assetArchives?.map((assetArchive) => ({
+			protocol: assetArchive.protocol,
+			archive: resolve(assetArchive.archive),
+			host: assetArchive.host,
+			spa_fallback: assetArchive.spaFallback,
+			integrity: assetArchive.integrity,
+		})),
+		navigation: options.navigation,
+		navigation_handler: options.navigationHandler !== undefined,
+		new_window_behavior: options.newWindowBehavior,
+		sync_window_title: options.syncWindowTitle,
+		headers: options.headers,
+		proxy: options.proxy,
+		user_agent: options.userAgent,
+		accept_language: options.acceptLanguage,
+		bounds: options.bounds,
 	};
 }
 